@@ -0,0 +1,70 @@
+use std::{env, fs, path::PathBuf};
+
+/// Board features and the name of their files in `memory/`.
+const BOARDS: &[(&str, &str)] = &[
+    ("CARGO_FEATURE_OLIMEX_H405", "olimex-h405"),
+    ("CARGO_FEATURE_LM3S6965EVB", "lm3s6965evb"),
+    ("CARGO_FEATURE_MICROBIT", "microbit"),
+    ("CARGO_FEATURE_MPS2_AN505", "mps2-an505"),
+];
+
+/// Features selecting the monotonic of the application, and the cfgs they set besides
+/// `profiled_mono`:
+/// - `overhead_mono`: profiled by the reference profilers, reading the board cycle counter;
+/// - `fine_systick_mono`: SysTick monotonic with a sub-tick `now_fine()`.
+const MONOTONICS: &[(&str, &[&str])] = &[
+    ("CARGO_FEATURE_SYSTICK", &["overhead_mono", "fine_systick_mono"]),
+    ("CARGO_FEATURE_SYSTICK_TICKLESS", &["overhead_mono"]),
+    ("CARGO_FEATURE_TIM2", &["overhead_mono"]),
+    ("CARGO_FEATURE_WAKE_LATENESS", &["fine_systick_mono"]),
+    ("CARGO_FEATURE_SYSTICK_WIDTH", &["fine_systick_mono"]),
+    ("CARGO_FEATURE_LOCK_FREE_SIGNAL", &["fine_systick_mono"]),
+    ("CARGO_FEATURE_DWT_MONO", &[]),
+];
+
+// Copy the memory layout (and the interrupt handlers definitions, for boards without a device
+// crate) of the selected board where the cortex-m-rt linker script can find them.
+fn main() {
+    let boards: Vec<&str> = BOARDS
+        .iter()
+        .filter(|(feature, _)| env::var_os(feature).is_some())
+        .map(|(_, board)| *board)
+        .collect();
+    let board = match boards.as_slice() {
+        [board] => *board,
+        [] => panic!("No board selected, enable one of the board features"),
+        _ => panic!("Multiple boards selected ({boards:?}), enable only one board feature"),
+    };
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::copy(format!("memory/{board}.x"), out.join("memory.x")).unwrap();
+    let device = PathBuf::from(format!("memory/{board}-device.x"));
+    if device.exists() {
+        fs::copy(device, out.join("device.x")).unwrap();
+    }
+    println!("cargo:rustc-link-search={}", out.display());
+
+    // Monotonic selection: `profiled_mono` if a feature selects a monotonic of
+    // profiled-rtic-monotonics, `multiple_monos` (rejected by `src/time.rs`) if several do
+    println!("cargo:rustc-check-cfg=cfg(profiled_mono, overhead_mono, fine_systick_mono, multiple_monos)");
+    let monotonics: Vec<&[&str]> = MONOTONICS
+        .iter()
+        .filter(|(feature, _)| env::var_os(feature).is_some())
+        .map(|(_, cfgs)| *cfgs)
+        .collect();
+    if !monotonics.is_empty() {
+        println!("cargo:rustc-cfg=profiled_mono");
+    }
+    if monotonics.len() > 1 {
+        println!("cargo:rustc-cfg=multiple_monos");
+    }
+    for cfg in monotonics.concat() {
+        println!("cargo:rustc-cfg={cfg}");
+    }
+
+    // Reported in the run metadata
+    println!("cargo:rustc-env=PROFILING_TARGET={}", env::var("TARGET").unwrap());
+
+    println!("cargo:rerun-if-changed=memory");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,6 @@
+PROVIDE(UART0RX = DefaultHandler);
+PROVIDE(UART0TX = DefaultHandler);
+PROVIDE(UART1RX = DefaultHandler);
+PROVIDE(UART1TX = DefaultHandler);
+PROVIDE(UART2RX = DefaultHandler);
+PROVIDE(UART2TX = DefaultHandler);
@@ -0,0 +1,11 @@
+MEMORY
+{
+  /* Secure aliases of SSRAM1 (code) and SSRAM2 (data) */
+  FLASH : ORIGIN = 0x10000000, LENGTH = 512K
+  RAM : ORIGIN = 0x38000000, LENGTH = 256K
+}
+
+/* This is where the call stack will be allocated. */
+/* The stack is of the full descending type. */
+/* NOTE Do NOT modify `_stack_start` unless you know what you are doing */
+_stack_start = ORIGIN(RAM) + LENGTH(RAM);
@@ -0,0 +1,375 @@
+//! [`Monotonic`](rtic_time::Monotonic) counting core clock cycles with the DWT cycle counter,
+//! woken up by Cortex-M SysTick.
+//!
+//! The instants are the 32-bit DWT `CYCCNT` register extended to 64 bits, so that `now()` has
+//! the resolution of the core clock and can be compared with cycle counts measured elsewhere,
+//! e.g. by a debug probe. SysTick is only used as the wake-up source: it is reprogrammed to the
+//! next deadline of the timer queue, and interrupts at least every 2^24 cycles to keep track of
+//! the `CYCCNT` wraps.
+//!
+//! The DWT cycle counter is only available on ARMv7-M and ARMv8-M Mainline, and is optional
+//! there: starting the monotonic panics if `DWT_CTRL.NOCYCCNT` is set. It is enabled when the
+//! monotonic is started and must not be written afterwards.
+//!
+//! # Example
+//!
+//! ```
+//! use rtic_monotonics::dwt_systick::prelude::*;
+//!
+//! // Create the type `Mono`, counting the cycles of a 12 MHz core clock.
+//! dwt_systick_monotonic!(Mono, 12_000_000);
+//!
+//! fn init() {
+//!     let core_peripherals = cortex_m::Peripherals::take().unwrap();
+//!     // Start the monotonic using the cortex-m crate's Systick driver.
+//!     // The core clock must match the rate of the monotonic.
+//!     Mono::start(core_peripherals.SYST, 12_000_000);
+//! }
+//!
+//! async fn usage() {
+//!     loop {
+//!          // You can use the monotonic to get the time...
+//!          let timestamp = Mono::now();
+//!          // ...and you can use it to add a delay to this async function
+//!          Mono::delay(100.millis()).await;
+//!     }
+//! }
+//! ```
+//!
+//! # Profiling
+//!
+//! With the `profiling` feature, the macro accepts a [profiler](crate::profiler), called with
+//! `InterruptKind::Compare` interrupts. `HandlerProfiler` measures the execution time of the
+//! interrupt handler, like the one of the tickless SysTick monotonic.
+
+/// Common definitions and traits for using the DWT monotonic
+pub mod prelude {
+    pub use crate::dwt_systick_monotonic;
+
+    pub use crate::Monotonic;
+
+    pub use fugit::{self, ExtU64, ExtU64Ceil};
+}
+
+pub use cortex_m::peripheral::SYST;
+
+#[cfg(feature = "profiling")]
+use crate::profiler::{
+    cycles_to_ns, CompareHook, CycleSource, InterruptKind, MonotonicProfiler, ProfileState,
+    ReleaseCounter,
+};
+use crate::TimerQueueBackend;
+use core::cell::Cell;
+#[cfg(feature = "profiling")]
+use core::marker::PhantomData;
+use cortex_m::interrupt::{self, Mutex};
+use cortex_m::peripheral::{DCB, DWT};
+use rtic_time::timer_queue::TimerQueue;
+
+/// Longest SysTick period, in cycles (24-bit reload value).
+const MAX_CYCLES: u32 = 0x0100_0000;
+/// Shortest SysTick period, so that the counter is not reloaded while being reprogrammed.
+const MIN_DELAY: u32 = 64;
+
+static DWT_TIMER_QUEUE: TimerQueue<DwtSystickBackend> = TimerQueue::new();
+#[cfg(feature = "profiling")]
+static DWT_COMPARE_HOOK: CompareHook = CompareHook::new();
+#[cfg(feature = "profiling")]
+static DWT_RELEASES: ReleaseCounter = ReleaseCounter::new();
+/// Last cycle count returned, to extend `CYCCNT` to 64 bits
+static DWT_CYCLES: Mutex<Cell<u64>> = Mutex::new(Cell::new(0));
+
+/// DWT based [`TimerQueueBackend`], woken up by SysTick.
+pub struct DwtSystickBackend;
+
+impl DwtSystickBackend {
+    /// Starts the monotonic timer.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Use the prelude macros instead.
+    pub fn _start(mut systick: SYST, sysclk: u32, timer_hz: u32) {
+        assert!(
+            sysclk == timer_hz,
+            "The rate of the DWT monotonic must be the core clock frequency."
+        );
+
+        // SAFETY: reading DWT_CTRL has no side effect.
+        let ctrl = unsafe { (*DWT::PTR).ctrl.read() };
+        assert!(
+            ctrl & (1 << 25) == 0, // NOCYCCNT
+            "The DWT of this core has no cycle counter."
+        );
+
+        // SAFETY: the cycle counter is only enabled, and never written, by the monotonic.
+        unsafe {
+            (*DCB::PTR).demcr.modify(|demcr| demcr | (1 << 24)); // TRCENA
+            DWT::unlock();
+            (*DWT::PTR).ctrl.modify(|ctrl| ctrl | 1); // CYCCNTENA
+        }
+        interrupt::free(|cs| DWT_CYCLES.borrow(cs).set(DWT::cycle_count() as u64));
+
+        systick.disable_counter();
+        systick.set_clock_source(cortex_m::peripheral::syst::SystClkSource::Core);
+        systick.set_reload(MAX_CYCLES - 1);
+        systick.clear_current();
+        systick.enable_interrupt();
+        systick.enable_counter();
+
+        DWT_TIMER_QUEUE.initialize(DwtSystickBackend {});
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Profiling state filled by [`HandlerProfiler`].
+    pub fn profile() -> &'static ProfileState {
+        &DWT_PROFILE
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Sets the [`MonotonicProfiler::on_compare_set`] hook.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Use the prelude macros instead.
+    ///
+    /// # Safety
+    ///
+    /// Must be called before [`DwtSystickBackend::_start`].
+    pub unsafe fn _set_compare_hook(hook: fn(u64)) {
+        DWT_COMPARE_HOOK.set(hook);
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Counter of the waiters released by the timer queue.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Use the prelude macros instead.
+    pub fn _release_counter() -> &'static ReleaseCounter {
+        &DWT_RELEASES
+    }
+
+    /// Cycle count extended to 64 bits, without accounting the read for the profiling.
+    fn cycles() -> u64 {
+        interrupt::free(|cs| {
+            let cycles = DWT_CYCLES.borrow(cs);
+            let last = cycles.get();
+            // SysTick interrupts at least every 2^24 cycles, so CYCCNT wrapped at most once
+            // since the last call.
+            let mut now = (last & !0xffff_ffff) | DWT::cycle_count() as u64;
+            if now < last {
+                now += 1 << 32;
+            }
+            cycles.set(now);
+            now
+        })
+    }
+
+    fn systick() -> SYST {
+        unsafe { core::mem::transmute::<(), SYST>(()) }
+    }
+}
+
+impl TimerQueueBackend for DwtSystickBackend {
+    type Ticks = u64;
+
+    fn now() -> Self::Ticks {
+        #[cfg(feature = "profiling")]
+        DWT_RELEASES.on_now();
+        Self::cycles()
+    }
+
+    fn set_compare(instant: Self::Ticks) {
+        let delay = instant
+            .saturating_sub(Self::cycles())
+            .clamp(MIN_DELAY as u64, MAX_CYCLES as u64) as u32;
+        let mut systick = Self::systick();
+        interrupt::free(|_| {
+            systick.set_reload(delay - 1);
+            // Also clears COUNTFLAG, the counter restarts from the new reload value
+            systick.clear_current();
+        });
+        #[cfg(feature = "profiling")]
+        DWT_RELEASES.on_compare(|| DWT_COMPARE_HOOK.call(instant));
+    }
+
+    fn clear_compare_flag() {
+        // NOOP with SysTick interrupt
+    }
+
+    fn pend_interrupt() {
+        cortex_m::peripheral::SCB::set_pendst();
+    }
+
+    fn on_interrupt() {
+        // Keep track of the CYCCNT wraps
+        Self::cycles();
+    }
+
+    fn disable_timer() {
+        // Keep extending CYCCNT, interrupting as seldom as possible
+        let mut systick = Self::systick();
+        interrupt::free(|_| {
+            systick.set_reload(MAX_CYCLES - 1);
+            systick.clear_current();
+        });
+    }
+
+    fn timer_queue() -> &'static TimerQueue<Self> {
+        &DWT_TIMER_QUEUE
+    }
+}
+
+/// Profiling state of the DWT monotonic.
+#[cfg(feature = "profiling")]
+static DWT_PROFILE: ProfileState = ProfileState::new();
+
+/// Reference [`MonotonicProfiler`] of the DWT monotonic, measuring the execution time of the
+/// interrupt handler, from entry to exit, with the cycle counter `C`.
+///
+/// The execution time is logged for every interrupt and recorded as the
+/// [`InterruptKind::Compare`] overhead of [`DwtSystickBackend::profile`], along with its
+/// breakdown by number of released waiters. Once the sample limit is reached, the statistics are
+/// reported before taking its [`LimitAction`](crate::profiler::LimitAction).
+#[cfg(feature = "profiling")]
+pub struct HandlerProfiler<C>(PhantomData<C>);
+
+#[cfg(feature = "profiling")]
+impl<C: CycleSource> MonotonicProfiler for HandlerProfiler<C> {
+    fn on_interrupt_enter(_kind: InterruptKind) {
+        DWT_PROFILE.set_entry_cycles(C::cycles());
+    }
+
+    fn on_interrupt_exit(kind: InterruptKind) {
+        let overhead = cycles_to_ns::<C>(C::cycles().wrapping_sub(DWT_PROFILE.entry_cycles()));
+        DWT_PROFILE.set_handler_overhead(overhead);
+        DWT_PROFILE.record_interrupt(kind, overhead);
+        defmt::info!("DWT SysTick handler: {} ns", overhead);
+        defmt::info!("----------------------------------");
+    }
+
+    fn on_wake(released: u32) {
+        if DWT_PROFILE.record_wake(released) {
+            DWT_PROFILE.end_window("DWT SysTick");
+        }
+    }
+}
+
+/// Create a DWT based monotonic and register the Systick interrupt for it.
+///
+/// This macro expands to produce a new type called `$name`, which has a `fn
+/// start()` function for you to call. The type has an implementation of the
+/// `rtic_monotonics::TimerQueueBasedMonotonic` trait, the
+/// `embedded_hal::delay::DelayNs` trait and the
+/// `embedded_hal_async::delay::DelayNs` trait.
+///
+/// This macro also produces an interrupt handler for the SysTick interrupt, by
+/// creating an `extern "C" fn SysTick() { ... }`: it cannot be used together with the other
+/// SysTick based monotonics.
+///
+/// See [`crate::dwt_systick`] for more details.
+///
+/// # Arguments
+///
+/// * `name` - The name that the monotonic type will have.
+/// * `core_clock_hz` - The core clock frequency, which is the rate of the monotonic.
+/// * `profiler = P` - The [`MonotonicProfiler`](crate::profiler::MonotonicProfiler) of the
+///   monotonic. Requires the `profiling` feature; see [profiling](crate::dwt_systick#profiling).
+#[macro_export]
+macro_rules! dwt_systick_monotonic {
+    ($name:ident, $core_clock_hz:expr) => {
+        $crate::__internal_create_dwt_systick_struct!($name, $core_clock_hz, {
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            unsafe extern "C" fn SysTick() {
+                use $crate::TimerQueueBackend;
+                $crate::dwt_systick::DwtSystickBackend::timer_queue().on_monotonic_interrupt();
+            }
+        });
+    };
+    ($name:ident, $core_clock_hz:expr, profiler = $profiler:ty) => {
+        $crate::__internal_create_profiled_dwt_systick_struct!($name, $core_clock_hz, $profiler);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(feature = "profiling")]
+#[macro_export]
+macro_rules! __internal_create_profiled_dwt_systick_struct {
+    ($name:ident, $core_clock_hz:expr, $profiler:ty) => {
+        $crate::__internal_create_dwt_systick_struct!($name, $core_clock_hz, {
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            unsafe extern "C" fn SysTick() {
+                use $crate::profiler::{InterruptKind, MonotonicProfiler};
+                use $crate::TimerQueueBackend;
+                <$profiler as MonotonicProfiler>::on_interrupt_enter(InterruptKind::Compare);
+                let released = $crate::dwt_systick::DwtSystickBackend::_release_counter()
+                    .count(|| $crate::dwt_systick::DwtSystickBackend::timer_queue().on_monotonic_interrupt());
+                <$profiler as MonotonicProfiler>::on_interrupt_exit(InterruptKind::Compare);
+                <$profiler as MonotonicProfiler>::on_wake(released);
+            }
+
+            // SAFETY: the timer queue is not initialized yet.
+            unsafe {
+                $crate::dwt_systick::DwtSystickBackend::_set_compare_hook(
+                    <$profiler as $crate::profiler::MonotonicProfiler>::on_compare_set,
+                )
+            };
+        });
+
+        $crate::__internal_create_profile_access!($name, $crate::dwt_systick::DwtSystickBackend);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "profiling"))]
+#[macro_export]
+macro_rules! __internal_create_profiled_dwt_systick_struct {
+    ($($tt:tt)*) => {
+        compile_error!("A profiler requires the `profiling` feature of profiled-rtic-monotonics");
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __internal_create_dwt_systick_struct {
+    ($name:ident, $core_clock_hz:expr, { $($setup:tt)* }) => {
+        /// A `Monotonic` based on the DWT cycle counter.
+        pub struct $name;
+
+        impl $name {
+            /// Starts the `Monotonic`.
+            ///
+            /// The `sysclk` parameter is the core clock frequency, at which both SysTick and the
+            /// DWT cycle counter run. This value should come from the clock generation function
+            /// of the used HAL.
+            ///
+            /// Panics if `sysclk` is not the rate of the monotonic.
+            ///
+            /// This method must be called only once.
+            pub fn start(systick: $crate::dwt_systick::SYST, sysclk: u32) {
+                $($setup)*
+
+                $crate::dwt_systick::DwtSystickBackend::_start(systick, sysclk, $core_clock_hz);
+            }
+        }
+
+        impl $crate::TimerQueueBasedMonotonic for $name {
+            type Backend = $crate::dwt_systick::DwtSystickBackend;
+            type Instant = $crate::fugit::Instant<
+                <Self::Backend as $crate::TimerQueueBackend>::Ticks,
+                1,
+                { $core_clock_hz },
+            >;
+            type Duration = $crate::fugit::Duration<
+                <Self::Backend as $crate::TimerQueueBackend>::Ticks,
+                1,
+                { $core_clock_hz },
+            >;
+        }
+
+        $crate::rtic_time::impl_embedded_hal_delay_fugit!($name);
+        $crate::rtic_time::impl_embedded_hal_async_delay_fugit!($name);
+    };
+}
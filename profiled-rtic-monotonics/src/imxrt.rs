@@ -205,6 +205,15 @@ macro_rules! make_timer {
             }
         }
 
+        #[cfg(feature = "profiling")]
+        impl $backend_name {
+            /// Late and missed half-period interrupts of this GPT.
+            pub fn half_period_monitor() -> &'static crate::profiler::HalfPeriodMonitor {
+                static MONITOR: crate::profiler::HalfPeriodMonitor = crate::profiler::HalfPeriodMonitor::new();
+                &MONITOR
+            }
+        }
+
         impl TimerQueueBackend for $backend_name {
             type Ticks = u64;
 
@@ -239,6 +248,7 @@ macro_rules! make_timer {
                 cortex_m::peripheral::NVIC::pend(ral::Interrupt::$timer);
             }
 
+            #[cfg(not(feature = "profiling"))]
             fn on_interrupt() {
                 let gpt = unsafe{ $timer::instance() };
 
@@ -257,6 +267,28 @@ macro_rules! make_timer {
                 }
             }
 
+            #[cfg(feature = "profiling")]
+            fn on_interrupt() {
+                let gpt = unsafe{ $timer::instance() };
+
+                let (rollover, half_rollover) = ral::read_reg!(ral::gpt, gpt, SR, ROV, OF1);
+
+                if rollover != 0 {
+                    ral::write_reg!(ral::gpt, gpt, SR, ROV: 1);
+                }
+                if half_rollover != 0 {
+                    ral::write_reg!(ral::gpt, gpt, SR, OF1: 1);
+                }
+                Self::half_period_monitor()._on_interrupt(
+                    $period.load(Ordering::Relaxed) as u64,
+                    rollover != 0,
+                    half_rollover != 0,
+                    0x8000_0000,
+                    |added| { $period.fetch_add(added, Ordering::Relaxed); },
+                    Self::now,
+                );
+            }
+
             fn timer_queue() -> &'static TimerQueue<Self> {
                 &$tq
             }
@@ -10,6 +10,18 @@
 //!
 //! # Cortex-M Systick
 //! The `systick` monotonic works on all Arm Cortex-M parts, and requires that the feature `cortex-m-systick` is enabled.
+//! The same feature enables the `systick_tickless` monotonic, which reprograms SysTick to the next deadline
+//! instead of interrupting at the tick rate.
+//!
+//! # Cortex-M DWT
+//! The `dwt_systick` monotonic counts core clock cycles with the DWT cycle counter, extended to 64 bits,
+//! and is woken up by SysTick. It works on ARMv7-M and ARMv8-M Mainline parts, and requires that the
+//! feature `cortex-m-dwt` is enabled.
+//!
+//! # Profiling
+//! With the `profiling` feature, the `systick`, `systick_tickless`, `dwt_systick` and STM32 monotonics accept a profiler,
+//! called on each monotonic interrupt, see `profiler`. It also makes the STM32, nRF and i.MX RT monotonics
+//! record missed half-period interrupts instead of panicking. Without it, this crate behaves as `rtic-monotonics`.
 //!
 //! # RP2040
 //! The RP2040 monotonics require that the `rp2040` feature is enabled.
@@ -48,7 +60,7 @@
 //! system.
 
 // To build these docs correctly:
-// RUSTFLAGS="--cfg docsrs" cargo +nightly doc --features thumbv7-backend,cortex-m-systick,rp2040,nrf52840,imxrt_gpt1,imxrt_gpt2,imxrt-ral/imxrt1011,stm32h725ag,stm32_tim2,stm32_tim3,stm32_tim4,stm32_tim5,stm32_tim15
+// RUSTFLAGS="--cfg docsrs" cargo +nightly doc --features thumbv7-backend,profiling,cortex-m-systick,cortex-m-dwt,rp2040,nrf52840,imxrt_gpt1,imxrt_gpt2,imxrt-ral/imxrt1011,stm32h725ag,stm32_tim2,stm32_tim3,stm32_tim4,stm32_tim5,stm32_tim15
 
 #![no_std]
 #![deny(missing_docs)]
@@ -61,6 +73,26 @@ pub use rtic_time::{
     TimeoutError,
 };
 
+#[cfg(all(
+    feature = "profiling",
+    any(
+        feature = "cortex-m-systick",
+        feature = "cortex-m-dwt",
+        feature = "imxrt",
+        feature = "nrf52805",
+        feature = "nrf52810",
+        feature = "nrf52811",
+        feature = "nrf52832",
+        feature = "nrf52833",
+        feature = "nrf52840",
+        feature = "nrf5340-app",
+        feature = "nrf5340-net",
+        feature = "nrf9160",
+        stm32,
+    )
+))]
+pub mod profiler;
+
 #[cfg(feature = "esp32c3-systimer")]
 pub mod esp32c3;
 
@@ -70,6 +102,12 @@ pub mod esp32c6;
 #[cfg(feature = "cortex-m-systick")]
 pub mod systick;
 
+#[cfg(feature = "cortex-m-systick")]
+pub mod systick_tickless;
+
+#[cfg(feature = "cortex-m-dwt")]
+pub mod dwt_systick;
+
 #[cfg(feature = "rp2040")]
 pub mod rp2040;
 
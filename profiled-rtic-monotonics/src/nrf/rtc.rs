@@ -240,6 +240,15 @@ macro_rules! make_rtc {
             }
         }
 
+        #[cfg(feature = "profiling")]
+        impl $backend_name {
+            /// Late and missed half-period interrupts of this RTC.
+            pub fn half_period_monitor() -> &'static $crate::profiler::HalfPeriodMonitor {
+                static MONITOR: $crate::profiler::HalfPeriodMonitor = $crate::profiler::HalfPeriodMonitor::new();
+                &MONITOR
+            }
+        }
+
         impl TimerQueueBackend for $backend_name {
             type Ticks = u64;
 
@@ -251,6 +260,7 @@ macro_rules! make_rtc {
                 )
             }
 
+            #[cfg(not(feature = "profiling"))]
             fn on_interrupt() {
                 let rtc = unsafe { &*$rtc::PTR };
                 if rtc.events_ovrflw.read().bits() == 1 {
@@ -265,6 +275,27 @@ macro_rules! make_rtc {
                 }
             }
 
+            #[cfg(feature = "profiling")]
+            fn on_interrupt() {
+                let rtc = unsafe { &*$rtc::PTR };
+                let overflow = rtc.events_ovrflw.read().bits() == 1;
+                if overflow {
+                    rtc.events_ovrflw.write(|w| unsafe { w.bits(0) });
+                }
+                let half_period = rtc.events_compare[1].read().bits() == 1;
+                if half_period {
+                    rtc.events_compare[1].write(|w| unsafe { w.bits(0) });
+                }
+                Self::half_period_monitor()._on_interrupt(
+                    $overflow.load(Ordering::Relaxed) as u64,
+                    overflow,
+                    half_period,
+                    0x80_0000,
+                    |added| { $overflow.fetch_add(added, Ordering::Relaxed); },
+                    Self::now,
+                );
+            }
+
             fn set_compare(mut instant: Self::Ticks) {
                 let rtc = unsafe { &*$rtc::PTR };
 
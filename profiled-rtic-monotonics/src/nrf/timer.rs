@@ -276,6 +276,15 @@ macro_rules! make_timer {
             }
         }
 
+        #[cfg(feature = "profiling")]
+        impl $backend_name {
+            /// Late and missed half-period interrupts of this timer.
+            pub fn half_period_monitor() -> &'static crate::profiler::HalfPeriodMonitor {
+                static MONITOR: crate::profiler::HalfPeriodMonitor = crate::profiler::HalfPeriodMonitor::new();
+                &MONITOR
+            }
+        }
+
         impl TimerQueueBackend for $backend_name {
             type Ticks = u64;
 
@@ -291,6 +300,7 @@ macro_rules! make_timer {
                 )
             }
 
+            #[cfg(not(feature = "profiling"))]
             fn on_interrupt() {
                 let timer = unsafe { &*$timer::PTR };
 
@@ -309,6 +319,29 @@ macro_rules! make_timer {
                 }
             }
 
+            #[cfg(feature = "profiling")]
+            fn on_interrupt() {
+                let timer = unsafe { &*$timer::PTR };
+
+                // A compare match on channel 1 is an overflow, on channel 2 a half-period overflow
+                let overflow = timer.events_compare[1].read().bits() & 1 != 0;
+                if overflow {
+                    timer.events_compare[1].write(|w| w);
+                }
+                let half_period = timer.events_compare[2].read().bits() & 1 != 0;
+                if half_period {
+                    timer.events_compare[2].write(|w| w);
+                }
+                Self::half_period_monitor()._on_interrupt(
+                    $overflow.load(Ordering::Relaxed) as u64,
+                    overflow,
+                    half_period,
+                    0x8000_0000,
+                    |added| { $overflow.fetch_add(added, Ordering::Relaxed); },
+                    Self::now,
+                );
+            }
+
             fn set_compare(instant: Self::Ticks) {
                 let timer = unsafe { &*$timer::PTR };
                 timer.cc[0].write(|w| unsafe { w.cc().bits(instant as u32) });
@@ -0,0 +1,702 @@
+//! Profiling hooks of the monotonics.
+//!
+//! The monotonic macros accept a `profiler = P` argument, where `P` implements
+//! [`MonotonicProfiler`]. The hooks of `P` are called from the interrupt handler and the timer
+//! queue backend of the monotonic, so that applications can collect, aggregate or forward the
+//! profiling data however they need. Without a profiler, the macros expand to the unprofiled
+//! monotonics.
+//!
+//! This module requires the `profiling` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use rtic_monotonics::profiler::{InterruptKind, MonotonicProfiler};
+//! use rtic_monotonics::systick::prelude::*;
+//!
+//! struct WakeCounter;
+//!
+//! impl MonotonicProfiler for WakeCounter {
+//!     fn on_wake(released: u32) {
+//!         defmt::info!("released {} tasks", released);
+//!     }
+//! }
+//!
+//! systick_monotonic!(Mono, 1_000, profiler = WakeCounter);
+//! ```
+//!
+//! Reference profilers reporting over defmt are provided next to the monotonics, see
+//! [`crate::systick::OverheadProfiler`], [`crate::systick_tickless::HandlerProfiler`] and the
+//! `TimXProfiler` types of [`crate::stm32`].
+//!
+//! # Profiling state
+//!
+//! The reference profilers record their statistics in the [`ProfileState`] of the monotonic,
+//! returned by `Mono::profile()`. It can be read at any time with [`ProfileState::snapshot`] and
+//! cleared with [`ProfileState::reset`]. The reference profilers report the statistics every
+//! [`ProfileState::sample_limit`] samples, which defaults to [`DEFAULT_SAMPLE_LIMIT`], then take
+//! the [`LimitAction`] of the state: stop the application, which is the default, or keep running
+//! for long measurements, optionally starting a new window of statistics:
+//!
+//! ```ignore
+//! Mono::start(cx.core.SYST, 16_000_000);
+//! Mono::profile().set_sample_limit(1_000);
+//! Mono::profile().set_limit_action(LimitAction::ReportAndReset);
+//! ```
+//!
+//! # Wake-up accounting
+//!
+//! The timer queue of `rtic-time` does not report how many waiters it releases, so the profiled
+//! monotonics count them from the calls the queue makes to their backend while the interrupt
+//! handler processes it: each examination of the head of the queue reads the time once, and a
+//! head not yet due is programmed as the next compare, then the time is read once more to check
+//! that it did not pass meanwhile. The released waiters are thus the time reads minus twice the
+//! compares. Every wait is accounted, whether issued through the [`Monotonic`](crate::Monotonic)
+//! trait, the `embedded-hal` delay traits or `rtic-sync`.
+//!
+//! This relies on the implementation of the timer queue of `rtic-time`, which is not part of its
+//! API: the dependency is pinned to the version checked, to be checked again before updating it.
+//! The number of pending entries of the queue is not reported: the queue does not expose it, and
+//! inserting a waiter calls the backend only when it becomes the head of the queue.
+
+use core::cell::{Cell, RefCell, UnsafeCell};
+use cortex_m::interrupt::{self, Mutex};
+use cortex_m::peripheral::SCB;
+use portable_atomic::{AtomicU32, Ordering};
+
+/// Kind of a monotonic interrupt.
+#[derive(Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum InterruptKind {
+    /// Periodic tick interrupt, e.g. of SysTick.
+    Tick = 0,
+    /// Half-period interrupt, extending the timer counter.
+    HalfPeriod = 1,
+    /// Compare interrupt, processing the timer queue, e.g. of a tickless timer. Also used when both
+    /// kinds are pending, and for interrupts pended in software by the timer queue.
+    Compare = 2,
+}
+
+/// Hooks called by a profiled monotonic.
+///
+/// All the hooks default to doing nothing, implement only the ones you need. They are called
+/// with the interrupt handler of the monotonic running, except [`on_compare_set`], which runs in
+/// the context that (re)arms the timer queue.
+///
+/// [`on_compare_set`]: MonotonicProfiler::on_compare_set
+pub trait MonotonicProfiler {
+    /// Called on entry of the monotonic interrupt handler, before the timer queue is processed.
+    fn on_interrupt_enter(_kind: InterruptKind) {}
+
+    /// Called once the timer queue has been processed, before the handler returns.
+    fn on_interrupt_exit(_kind: InterruptKind) {}
+
+    /// Called after [`on_interrupt_exit`](MonotonicProfiler::on_interrupt_exit), with the number
+    /// of waiters released by the timer queue during the interrupt. See
+    /// [wake-up accounting](self#wake-up-accounting).
+    fn on_wake(_released: u32) {}
+
+    /// Called when the timer queue programs the timer for its next deadline, in ticks of the
+    /// monotonic.
+    fn on_compare_set(_instant: u64) {}
+}
+
+/// Profiler doing nothing, e.g. to measure the cost of the instrumentation itself.
+pub struct NoProfiler;
+
+impl MonotonicProfiler for NoProfiler {}
+
+/// A free-running `u32` cycle counter, used by the reference profilers to timestamp events.
+///
+/// Usually the DWT CYCCNT register, or a hardware timer on cores lacking it (ARMv6-M).
+pub trait CycleSource {
+    /// Current value of the counter.
+    fn cycles() -> u32;
+
+    /// Rate of the counter, in MHz.
+    fn rate_mhz() -> u32;
+}
+
+/// Converts a number of cycles of `C` into nanoseconds, without relying on an FPU.
+pub(crate) fn cycles_to_ns<C: CycleSource>(cycles: u32) -> u32 {
+    (cycles as u64 * 1_000 / C::rate_mhz() as u64) as u32
+}
+
+/// The [`MonotonicProfiler::on_compare_set`] hook of a timer queue backend.
+///
+/// **Do not use this type directly.**
+#[doc(hidden)]
+pub struct CompareHook(UnsafeCell<fn(u64)>);
+
+// SAFETY: the hook is only written before the monotonic is started, see `CompareHook::set`.
+unsafe impl Sync for CompareHook {}
+
+impl CompareHook {
+    /// Creates a hook doing nothing.
+    pub const fn new() -> Self {
+        fn noop(_: u64) {}
+        Self(UnsafeCell::new(noop))
+    }
+
+    /// Sets the hook.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called while the backend may call the hook, i.e. only before the timer
+    /// queue is initialized.
+    pub unsafe fn set(&self, hook: fn(u64)) {
+        *self.0.get() = hook;
+    }
+
+    /// Calls the hook.
+    pub fn call(&self, instant: u64) {
+        // SAFETY: see `CompareHook::set`.
+        unsafe { (*self.0.get())(instant) }
+    }
+}
+
+impl Default for CompareHook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Number of [`WakeBreakdown`] buckets. The last one gathers the interrupts releasing
+/// `RELEASED_BUCKETS - 1` waiters or more.
+pub const RELEASED_BUCKETS: usize = 8;
+
+/// Interrupt handler overhead of the interrupts releasing a given number of waiters.
+#[derive(Clone, Copy)]
+pub struct ReleasedStats {
+    /// Number of measurements
+    pub count: u32,
+    /// Sum of the overheads in ns, to compute the mean
+    pub total_ns: u64,
+    /// Worst-case overhead in ns
+    pub wc_ns: u32,
+}
+
+impl ReleasedStats {
+    const fn new() -> Self {
+        Self {
+            count: 0,
+            total_ns: 0,
+            wc_ns: 0,
+        }
+    }
+
+    /// Mean overhead in ns, 0 without measurements.
+    pub fn mean_ns(&self) -> u32 {
+        if self.count == 0 {
+            0
+        } else {
+            (self.total_ns / self.count as u64) as u32
+        }
+    }
+}
+
+/// Interrupt handler overhead broken down by number of released waiters, to fit a cost model of
+/// the form `base + k * released`.
+///
+/// The overhead of an interrupt is given with [`WakeBreakdown::set_overhead`] from
+/// [`MonotonicProfiler::on_interrupt_exit`], then attributed to its number of released waiters
+/// with [`WakeBreakdown::record`] from [`MonotonicProfiler::on_wake`].
+#[derive(Clone, Copy)]
+pub struct WakeBreakdown {
+    overhead: u32,
+    buckets: [ReleasedStats; RELEASED_BUCKETS],
+}
+
+impl WakeBreakdown {
+    /// Creates an empty breakdown.
+    pub const fn new() -> Self {
+        Self {
+            overhead: 0,
+            buckets: [ReleasedStats::new(); RELEASED_BUCKETS],
+        }
+    }
+
+    /// Sets the overhead in ns of the current interrupt.
+    pub fn set_overhead(&mut self, overhead: u32) {
+        self.overhead = overhead;
+    }
+
+    /// Records the overhead of the current interrupt, which released `released` waiters.
+    pub fn record(&mut self, released: u32) {
+        let bucket = &mut self.buckets[(released as usize).min(RELEASED_BUCKETS - 1)];
+        bucket.count += 1;
+        bucket.total_ns += self.overhead as u64;
+        bucket.wc_ns = bucket.wc_ns.max(self.overhead);
+    }
+
+    /// Statistics indexed by number of released waiters.
+    pub fn buckets(&self) -> &[ReleasedStats; RELEASED_BUCKETS] {
+        &self.buckets
+    }
+
+    /// Logs the statistics of the non-empty buckets.
+    pub fn report(&self, source: &str) {
+        for (released, bucket) in self.buckets.iter().enumerate() {
+            if bucket.count == 0 {
+                continue;
+            }
+            defmt::info!(
+                "{} released {}{}: {} samples, mean {} ns, worst-case {} ns",
+                source,
+                released,
+                if released == RELEASED_BUCKETS - 1 { "+" } else { "" },
+                bucket.count,
+                bucket.mean_ns(),
+                bucket.wc_ns,
+            );
+        }
+    }
+}
+
+impl Default for WakeBreakdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Default sample limit of a [`ProfileState`].
+pub const DEFAULT_SAMPLE_LIMIT: u32 = 500;
+
+/// Action taken by the reference profilers when the sample limit of a [`ProfileState`] is reached,
+/// after reporting the statistics.
+#[derive(Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum LimitAction {
+    /// Stop the application with a panic.
+    Stop,
+    /// Keep accumulating the statistics, reporting them again every sample limit.
+    Report,
+    /// Reset the statistics, so that each report covers a window of sample limit samples.
+    ReportAndReset,
+}
+
+/// Overhead measured for an [`InterruptKind`].
+#[derive(Clone, Copy)]
+pub struct InterruptStats {
+    /// Number of measurements
+    pub count: u32,
+    /// Last overhead in ns
+    pub last_ns: u32,
+    /// Worst-case overhead in ns
+    pub wc_ns: u32,
+    /// Sum of the overheads in ns, to compute the mean
+    pub total_ns: u64,
+}
+
+impl InterruptStats {
+    const fn new() -> Self {
+        Self {
+            count: 0,
+            last_ns: 0,
+            wc_ns: 0,
+            total_ns: 0,
+        }
+    }
+
+    /// Mean overhead in ns, 0 without measurements.
+    pub fn mean_ns(&self) -> u32 {
+        if self.count == 0 {
+            0
+        } else {
+            (self.total_ns / self.count as u64) as u32
+        }
+    }
+}
+
+/// Consistent copy of the statistics of a [`ProfileState`].
+#[derive(Clone, Copy)]
+pub struct ProfileSnapshot {
+    /// Number of profiled interrupts
+    pub samples: u32,
+    /// Overhead per interrupt kind, see [`ProfileSnapshot::interrupt`]
+    pub interrupts: [InterruptStats; 3],
+    /// Handler overhead broken down by number of released waiters
+    pub wakes: WakeBreakdown,
+}
+
+impl ProfileSnapshot {
+    const fn new() -> Self {
+        Self {
+            samples: 0,
+            interrupts: [InterruptStats::new(); 3],
+            wakes: WakeBreakdown::new(),
+        }
+    }
+
+    /// Overhead of the interrupts of the given kind.
+    pub fn interrupt(&self, kind: InterruptKind) -> &InterruptStats {
+        &self.interrupts[kind as usize]
+    }
+
+    /// Logs the statistics.
+    pub fn report(&self, source: &str) {
+        for kind in [InterruptKind::Tick, InterruptKind::HalfPeriod, InterruptKind::Compare] {
+            let stats = self.interrupt(kind);
+            if stats.count == 0 {
+                continue;
+            }
+            defmt::info!(
+                "Worst-case {} {} overhead: {} ns, mean {} ns ({} samples)",
+                source,
+                kind,
+                stats.wc_ns,
+                stats.mean_ns(),
+                stats.count,
+            );
+        }
+        self.wakes.report(source);
+    }
+}
+
+struct ProfileData {
+    sample_limit: u32,
+    limit_action: LimitAction,
+    entry_cycles: u32,
+    previous_cycles: Option<u32>,
+    stats: ProfileSnapshot,
+}
+
+/// Profiling state of a monotonic, filled by its reference profiler.
+///
+/// The state is updated by the interrupt handler of the monotonic and can be read consistently
+/// from any context with [`ProfileState::snapshot`]. It is obtained with the `profile()`
+/// function of the monotonics created with a reference profiler, e.g. `Mono::profile()`.
+pub struct ProfileState {
+    data: Mutex<RefCell<ProfileData>>,
+}
+
+impl ProfileState {
+    /// Creates an empty state, with the [`DEFAULT_SAMPLE_LIMIT`], stopping at the limit.
+    pub const fn new() -> Self {
+        Self {
+            data: Mutex::new(RefCell::new(ProfileData {
+                sample_limit: DEFAULT_SAMPLE_LIMIT,
+                limit_action: LimitAction::Stop,
+                entry_cycles: 0,
+                previous_cycles: None,
+                stats: ProfileSnapshot::new(),
+            })),
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut ProfileData) -> R) -> R {
+        interrupt::free(|cs| f(&mut self.data.borrow(cs).borrow_mut()))
+    }
+
+    /// Copies the statistics.
+    pub fn snapshot(&self) -> ProfileSnapshot {
+        self.with(|data| data.stats)
+    }
+
+    /// Clears the statistics, keeping the sample limit and the limit action.
+    pub fn reset(&self) {
+        self.with(|data| data.stats = ProfileSnapshot::new())
+    }
+
+    /// Number of samples after which the reference profiler reports the statistics, then takes
+    /// the [`ProfileState::limit_action`]. 0 means no limit.
+    pub fn sample_limit(&self) -> u32 {
+        self.with(|data| data.sample_limit)
+    }
+
+    /// Sets the sample limit, see [`ProfileState::sample_limit`].
+    pub fn set_sample_limit(&self, sample_limit: u32) {
+        self.with(|data| data.sample_limit = sample_limit)
+    }
+
+    /// Action taken when the sample limit is reached.
+    pub fn limit_action(&self) -> LimitAction {
+        self.with(|data| data.limit_action)
+    }
+
+    /// Sets the limit action, see [`ProfileState::limit_action`].
+    pub fn set_limit_action(&self, limit_action: LimitAction) {
+        self.with(|data| data.limit_action = limit_action)
+    }
+
+    /// Records the cycle count at the handler entry.
+    pub fn set_entry_cycles(&self, cycles: u32) {
+        self.with(|data| data.entry_cycles = cycles)
+    }
+
+    /// Cycle count recorded at the handler entry.
+    pub fn entry_cycles(&self) -> u32 {
+        self.with(|data| data.entry_cycles)
+    }
+
+    /// Records `cycles` as the last timestamp, returning the previous one, if any.
+    pub fn replace_previous_cycles(&self, cycles: u32) -> Option<u32> {
+        self.with(|data| data.previous_cycles.replace(cycles))
+    }
+
+    /// Records the overhead in ns of an interrupt of the given kind.
+    pub fn record_interrupt(&self, kind: InterruptKind, overhead: u32) {
+        self.with(|data| {
+            let stats = &mut data.stats.interrupts[kind as usize];
+            stats.count += 1;
+            stats.last_ns = overhead;
+            stats.wc_ns = stats.wc_ns.max(overhead);
+            stats.total_ns += overhead as u64;
+        })
+    }
+
+    /// Records the handler overhead in ns of the current interrupt, see
+    /// [`WakeBreakdown::set_overhead`].
+    pub fn set_handler_overhead(&self, overhead: u32) {
+        self.with(|data| data.stats.wakes.set_overhead(overhead))
+    }
+
+    /// Records the released waiters of the current interrupt, which completes its sample.
+    /// Returns whether the sample limit is reached, which happens every sample limit samples.
+    pub fn record_wake(&self, released: u32) -> bool {
+        self.with(|data| {
+            data.stats.wakes.record(released);
+            data.stats.samples += 1;
+            data.sample_limit != 0 && data.stats.samples % data.sample_limit == 0
+        })
+    }
+
+    /// Reports the statistics, then takes the limit action.
+    pub(crate) fn end_window(&self, source: &str) {
+        self.snapshot().report(source);
+        match self.limit_action() {
+            LimitAction::Stop => defmt::panic!("End of {} measurements, stopping.", source),
+            LimitAction::Report => {}
+            LimitAction::ReportAndReset => self.reset(),
+        }
+    }
+}
+
+impl Default for ProfileState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Half-period events of a monotonic extending its timer counter, see [`HalfPeriodMonitor`].
+#[derive(Clone, Copy)]
+pub struct HalfPeriodStats {
+    /// Number of half periods accounted
+    pub events: u32,
+    /// Number of interrupts handling both the half-period and the overflow events at once, i.e.
+    /// running more than a half period late
+    pub late: u32,
+    /// Number of events lost, detected from the parity of the period counter
+    pub missed: u32,
+    /// Time of the last late or missed event, in ticks of the monotonic
+    pub last_anomaly: Option<u64>,
+    /// Lateness of the last interrupt after its first event, in ticks of the monotonic
+    pub last_lateness: u64,
+    /// Worst-case lateness, in ticks of the monotonic
+    pub wc_lateness: u64,
+}
+
+impl HalfPeriodStats {
+    const fn new() -> Self {
+        Self {
+            events: 0,
+            late: 0,
+            missed: 0,
+            last_anomaly: None,
+            last_lateness: 0,
+            wc_lateness: 0,
+        }
+    }
+
+    /// Logs the statistics.
+    pub fn report(&self, source: &str) {
+        defmt::info!(
+            "{} half periods: {} events, {} late, {} missed (last at {} ticks), worst-case lateness {} ticks",
+            source,
+            self.events,
+            self.late,
+            self.missed,
+            self.last_anomaly,
+            self.wc_lateness,
+        );
+    }
+}
+
+/// Monitor of the half-period events of a monotonic, which extend its timer counter.
+///
+/// Without the `profiling` feature, the half-period monotonics panic when their period counter
+/// shows that an interrupt was missed, as `rtic-monotonics` does. With it, the period counter is
+/// corrected instead, and the late and missed events are recorded here along with the lateness
+/// of the interrupt handler, so that long critical sections can be diagnosed. The monitor of a
+/// backend is returned by its `half_period_monitor()` function.
+pub struct HalfPeriodMonitor {
+    stats: Mutex<Cell<HalfPeriodStats>>,
+}
+
+impl HalfPeriodMonitor {
+    /// Creates an empty monitor.
+    pub const fn new() -> Self {
+        Self {
+            stats: Mutex::new(Cell::new(HalfPeriodStats::new())),
+        }
+    }
+
+    /// Copies the statistics.
+    pub fn snapshot(&self) -> HalfPeriodStats {
+        interrupt::free(|cs| self.stats.borrow(cs).get())
+    }
+
+    /// Clears the statistics.
+    pub fn reset(&self) {
+        interrupt::free(|cs| self.stats.borrow(cs).set(HalfPeriodStats::new()))
+    }
+
+    /// Accounts the pending `overflow` and `half_period` events of the interrupt handler.
+    ///
+    /// `periods` is the period counter, which is even during the first half of the timer
+    /// period, `advance` adds the given number of half periods to it, and `now` then reads the
+    /// time, where the half periods last `half_period_ticks`.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Used by the half-period monotonics.
+    pub fn _on_interrupt(
+        &self,
+        periods: u64,
+        overflow: bool,
+        half_period: bool,
+        half_period_ticks: u64,
+        advance: impl FnOnce(u32),
+        now: impl FnOnce() -> u64,
+    ) {
+        let pending = overflow as u32 + half_period as u32;
+        if pending == 0 {
+            return;
+        }
+        // A single event must be the one expected from the parity of the counter, otherwise
+        // the other one was lost before it.
+        let missed = pending == 1 && half_period == (periods % 2 == 1);
+        let added = if missed { 2 } else { pending };
+        advance(added);
+
+        let now = now();
+        let lateness = now.saturating_sub((periods + 1) * half_period_ticks);
+        interrupt::free(|cs| {
+            let cell = self.stats.borrow(cs);
+            let mut stats = cell.get();
+            stats.events += added;
+            if pending == 2 {
+                stats.late += 1;
+            }
+            if missed {
+                stats.missed += 1;
+            }
+            if pending == 2 || missed {
+                stats.last_anomaly = Some(now);
+            }
+            stats.last_lateness = lateness;
+            stats.wc_lateness = stats.wc_lateness.max(lateness);
+            cell.set(stats);
+        });
+    }
+}
+
+impl Default for HalfPeriodMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `ReleaseCounter::vector` while no interrupt handler processes the timer queue.
+const NOT_COUNTING: u32 = u32::MAX;
+
+/// Exception number of the active handler, 0 in thread mode.
+fn active_vector() -> u32 {
+    // SAFETY: read-only access to ICSR
+    unsafe { (*SCB::PTR).icsr.read() & 0x1ff }
+}
+
+/// Counter of the waiters released by the timer queue of a backend, see
+/// [wake-up accounting](self#wake-up-accounting).
+///
+/// The count assumes the processing of the queue by `TimerQueue::on_monotonic_interrupt` of the
+/// pinned `rtic-time` 2.0.1: one time read per head examined, and a compare programmed for the
+/// head not yet due, followed by one more time read. Another version may read the time a
+/// different number of times, which would skew the count.
+///
+/// **Do not use this type directly.**
+#[doc(hidden)]
+pub struct ReleaseCounter {
+    /// Exception number of the handler processing the timer queue, so that the time read by
+    /// preempting handlers is not accounted
+    vector: AtomicU32,
+    reads: AtomicU32,
+    compares: AtomicU32,
+}
+
+impl ReleaseCounter {
+    /// Creates a counter, not counting.
+    pub const fn new() -> Self {
+        Self {
+            vector: AtomicU32::new(NOT_COUNTING),
+            reads: AtomicU32::new(0),
+            compares: AtomicU32::new(0),
+        }
+    }
+
+    /// Runs `process`, which processes the timer queue, returning the number of waiters it
+    /// released.
+    pub fn count(&self, process: impl FnOnce()) -> u32 {
+        self.reads.store(0, Ordering::Relaxed);
+        self.compares.store(0, Ordering::Relaxed);
+        self.vector.store(active_vector(), Ordering::Relaxed);
+        process();
+        self.vector.store(NOT_COUNTING, Ordering::Relaxed);
+        let reads = self.reads.load(Ordering::Relaxed);
+        reads.saturating_sub(2 * self.compares.load(Ordering::Relaxed))
+    }
+
+    /// Accounts a read of the time by the timer queue.
+    pub fn on_now(&self) {
+        if self.counting() {
+            self.reads.store(self.reads.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+        }
+    }
+
+    /// Accounts a compare programmed by the timer queue, then runs the compare `hook`, whose
+    /// time reads are not accounted.
+    pub fn on_compare(&self, hook: impl FnOnce()) {
+        if !self.counting() {
+            hook();
+            return;
+        }
+        self.compares.store(self.compares.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+        let vector = self.vector.load(Ordering::Relaxed);
+        self.vector.store(NOT_COUNTING, Ordering::Relaxed);
+        hook();
+        self.vector.store(vector, Ordering::Relaxed);
+    }
+
+    fn counting(&self) -> bool {
+        let vector = self.vector.load(Ordering::Relaxed);
+        vector != NOT_COUNTING && vector == active_vector()
+    }
+}
+
+impl Default for ReleaseCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __internal_create_profile_access {
+    ($name:ident, $backend:ty) => {
+        impl $name {
+            /// Profiling state filled by the reference profiler of the monotonic, see
+            /// [`ProfileState`]($crate::profiler::ProfileState).
+            pub fn profile() -> &'static $crate::profiler::ProfileState {
+                <$backend>::profile()
+            }
+        }
+    };
+}
@@ -34,6 +34,37 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Profiling
+//!
+//! With the `profiling` feature, a [profiler](crate::profiler) can be given as the last argument
+//! of the macros. Each timer comes with a reference profiler, e.g. `Tim2Profiler` for TIM2,
+//! measuring the execution time of the interrupt handler, from entry to exit, with a
+//! `CycleSource`. It is reported over defmt separately for half-period (timer overflow)
+//! interrupts and compare (timer queue) interrupts:
+//!
+//! ```
+//! use rtic_monotonics::profiler::CycleSource;
+//! use rtic_monotonics::stm32::{prelude::*, Tim2Profiler};
+//!
+//! struct Dwt;
+//!
+//! impl CycleSource for Dwt {
+//!     fn cycles() -> u32 {
+//!         cortex_m::peripheral::DWT::cycle_count()
+//!     }
+//!
+//!     fn rate_mhz() -> u32 {
+//!         168
+//!     }
+//! }
+//!
+//! stm32_tim2_monotonic!(Mono, 1_000_000, profiler = Tim2Profiler<Dwt>);
+//! ```
+//!
+//! The `profiling` feature also makes missed half-period interrupts recoverable: instead of
+//! panicking, they are recorded in the [`HalfPeriodMonitor`](crate::profiler::HalfPeriodMonitor)
+//! of the backend, e.g. `Tim2Backend::half_period_monitor()`, whether or not a profiler is given.
 
 /// Common definitions and traits for using the STM32 monotonics
 pub mod prelude {
@@ -63,6 +94,14 @@ use rtic_time::{
 };
 use stm32_metapac as pac;
 
+#[cfg(feature = "profiling")]
+use crate::profiler::{
+    cycles_to_ns, CompareHook, CycleSource, HalfPeriodMonitor, InterruptKind, MonotonicProfiler,
+    ProfileState, ReleaseCounter,
+};
+#[cfg(feature = "profiling")]
+use core::marker::PhantomData;
+
 mod _generated {
     #![allow(dead_code)]
     #![allow(unused_imports)]
@@ -84,10 +123,51 @@ macro_rules! __internal_create_stm32_timer_interrupt {
     };
 }
 
+#[doc(hidden)]
+#[cfg(feature = "profiling")]
+#[macro_export]
+macro_rules! __internal_create_profiled_stm32_timer_struct {
+    ($name:ident, $mono_backend:ident, $timer:ident, $tick_rate_hz:expr, $profiler:ty) => {
+        $crate::__internal_create_stm32_timer_struct!($name, $mono_backend, $tick_rate_hz, {
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            unsafe extern "C" fn $timer() {
+                use $crate::profiler::MonotonicProfiler;
+                use $crate::TimerQueueBackend;
+                // Classify before the timer queue clears the flags
+                let kind = $crate::stm32::$mono_backend::_pending_interrupt_kind();
+                <$profiler as MonotonicProfiler>::on_interrupt_enter(kind);
+                let released = $crate::stm32::$mono_backend::_release_counter()
+                    .count(|| $crate::stm32::$mono_backend::timer_queue().on_monotonic_interrupt());
+                <$profiler as MonotonicProfiler>::on_interrupt_exit(kind);
+                <$profiler as MonotonicProfiler>::on_wake(released);
+            }
+
+            // SAFETY: the timer queue is not initialized yet.
+            unsafe {
+                $crate::stm32::$mono_backend::_set_compare_hook(
+                    <$profiler as $crate::profiler::MonotonicProfiler>::on_compare_set,
+                )
+            };
+        });
+
+        $crate::__internal_create_profile_access!($name, $crate::stm32::$mono_backend);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "profiling"))]
+#[macro_export]
+macro_rules! __internal_create_profiled_stm32_timer_struct {
+    ($($tt:tt)*) => {
+        compile_error!("A profiler requires the `profiling` feature of profiled-rtic-monotonics");
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __internal_create_stm32_timer_struct {
-    ($name:ident, $mono_backend:ident, $timer:ident, $tick_rate_hz:expr) => {
+    ($name:ident, $mono_backend:ident, $tick_rate_hz:expr, { $($setup:tt)* }) => {
         /// A `Monotonic` based on an STM32 timer peripheral.
         pub struct $name;
 
@@ -101,7 +181,7 @@ macro_rules! __internal_create_stm32_timer_struct {
             ///
             /// This method must be called only once.
             pub fn start(tim_clock_hz: u32) {
-                $crate::__internal_create_stm32_timer_interrupt!($mono_backend, $timer);
+                $($setup)*
 
                 $crate::stm32::$mono_backend::_start(tim_clock_hz, $tick_rate_hz);
             }
@@ -134,12 +214,19 @@ macro_rules! __internal_create_stm32_timer_struct {
 ///
 /// * `name` - The name that the monotonic type will have.
 /// * `tick_rate_hz` - The tick rate of the timer peripheral.
+/// * `profiler = P` - The [`MonotonicProfiler`](crate::profiler::MonotonicProfiler) of the
+///   monotonic. Requires the `profiling` feature; see [profiling](crate::stm32#profiling).
 ///
 #[cfg(feature = "stm32_tim2")]
 #[macro_export]
 macro_rules! stm32_tim2_monotonic {
     ($name:ident, $tick_rate_hz:expr) => {
-        $crate::__internal_create_stm32_timer_struct!($name, Tim2Backend, TIM2, $tick_rate_hz);
+        $crate::__internal_create_stm32_timer_struct!($name, Tim2Backend, $tick_rate_hz, {
+            $crate::__internal_create_stm32_timer_interrupt!(Tim2Backend, TIM2);
+        });
+    };
+    ($name:ident, $tick_rate_hz:expr, profiler = $profiler:ty) => {
+        $crate::__internal_create_profiled_stm32_timer_struct!($name, Tim2Backend, TIM2, $tick_rate_hz, $profiler);
     };
 }
 
@@ -151,12 +238,19 @@ macro_rules! stm32_tim2_monotonic {
 ///
 /// * `name` - The name that the monotonic type will have.
 /// * `tick_rate_hz` - The tick rate of the timer peripheral.
+/// * `profiler = P` - The [`MonotonicProfiler`](crate::profiler::MonotonicProfiler) of the
+///   monotonic. Requires the `profiling` feature; see [profiling](crate::stm32#profiling).
 ///
 #[cfg(feature = "stm32_tim3")]
 #[macro_export]
 macro_rules! stm32_tim3_monotonic {
     ($name:ident, $tick_rate_hz:expr) => {
-        $crate::__internal_create_stm32_timer_struct!($name, Tim3Backend, TIM3, $tick_rate_hz);
+        $crate::__internal_create_stm32_timer_struct!($name, Tim3Backend, $tick_rate_hz, {
+            $crate::__internal_create_stm32_timer_interrupt!(Tim3Backend, TIM3);
+        });
+    };
+    ($name:ident, $tick_rate_hz:expr, profiler = $profiler:ty) => {
+        $crate::__internal_create_profiled_stm32_timer_struct!($name, Tim3Backend, TIM3, $tick_rate_hz, $profiler);
     };
 }
 
@@ -168,12 +262,19 @@ macro_rules! stm32_tim3_monotonic {
 ///
 /// * `name` - The name that the monotonic type will have.
 /// * `tick_rate_hz` - The tick rate of the timer peripheral.
+/// * `profiler = P` - The [`MonotonicProfiler`](crate::profiler::MonotonicProfiler) of the
+///   monotonic. Requires the `profiling` feature; see [profiling](crate::stm32#profiling).
 ///
 #[cfg(feature = "stm32_tim4")]
 #[macro_export]
 macro_rules! stm32_tim4_monotonic {
     ($name:ident, $tick_rate_hz:expr) => {
-        $crate::__internal_create_stm32_timer_struct!($name, Tim4Backend, TIM4, $tick_rate_hz);
+        $crate::__internal_create_stm32_timer_struct!($name, Tim4Backend, $tick_rate_hz, {
+            $crate::__internal_create_stm32_timer_interrupt!(Tim4Backend, TIM4);
+        });
+    };
+    ($name:ident, $tick_rate_hz:expr, profiler = $profiler:ty) => {
+        $crate::__internal_create_profiled_stm32_timer_struct!($name, Tim4Backend, TIM4, $tick_rate_hz, $profiler);
     };
 }
 
@@ -185,12 +286,19 @@ macro_rules! stm32_tim4_monotonic {
 ///
 /// * `name` - The name that the monotonic type will have.
 /// * `tick_rate_hz` - The tick rate of the timer peripheral.
+/// * `profiler = P` - The [`MonotonicProfiler`](crate::profiler::MonotonicProfiler) of the
+///   monotonic. Requires the `profiling` feature; see [profiling](crate::stm32#profiling).
 ///
 #[cfg(feature = "stm32_tim5")]
 #[macro_export]
 macro_rules! stm32_tim5_monotonic {
     ($name:ident, $tick_rate_hz:expr) => {
-        $crate::__internal_create_stm32_timer_struct!($name, Tim5Backend, TIM5, $tick_rate_hz);
+        $crate::__internal_create_stm32_timer_struct!($name, Tim5Backend, $tick_rate_hz, {
+            $crate::__internal_create_stm32_timer_interrupt!(Tim5Backend, TIM5);
+        });
+    };
+    ($name:ident, $tick_rate_hz:expr, profiler = $profiler:ty) => {
+        $crate::__internal_create_profiled_stm32_timer_struct!($name, Tim5Backend, TIM5, $tick_rate_hz, $profiler);
     };
 }
 
@@ -202,17 +310,24 @@ macro_rules! stm32_tim5_monotonic {
 ///
 /// * `name` - The name that the monotonic type will have.
 /// * `tick_rate_hz` - The tick rate of the timer peripheral.
+/// * `profiler = P` - The [`MonotonicProfiler`](crate::profiler::MonotonicProfiler) of the
+///   monotonic. Requires the `profiling` feature; see [profiling](crate::stm32#profiling).
 ///
 #[cfg(feature = "stm32_tim15")]
 #[macro_export]
 macro_rules! stm32_tim15_monotonic {
     ($name:ident, $tick_rate_hz:expr) => {
-        $crate::__internal_create_stm32_timer_struct!($name, Tim15Backend, TIM15, $tick_rate_hz);
+        $crate::__internal_create_stm32_timer_struct!($name, Tim15Backend, $tick_rate_hz, {
+            $crate::__internal_create_stm32_timer_interrupt!(Tim15Backend, TIM15);
+        });
+    };
+    ($name:ident, $tick_rate_hz:expr, profiler = $profiler:ty) => {
+        $crate::__internal_create_profiled_stm32_timer_struct!($name, Tim15Backend, TIM15, $tick_rate_hz, $profiler);
     };
 }
 
 macro_rules! make_timer {
-    ($backend_name:ident, $timer:ident, $bits:ident, $overflow:ident, $tq:ident$(, doc: ($($doc:tt)*))?) => {
+    ($backend_name:ident, $profiler_name:ident, $timer:ident, $bits:ident, $overflow:ident, $tq:ident, $profile:ident$(, doc: ($($doc:tt)*))?) => {
         /// Monotonic timer backend implementation.
         $(
             #[cfg_attr(docsrs, doc(cfg($($doc)*)))]
@@ -224,8 +339,51 @@ macro_rules! make_timer {
 
         static $overflow: AtomicU64 = AtomicU64::new(0);
         static $tq: TimerQueue<$backend_name> = TimerQueue::new();
+        #[cfg(feature = "profiling")]
+        static $profile: ProfileState = ProfileState::new();
+
+        /// Reference [`MonotonicProfiler`] of the monotonic of this timer, measuring the
+        /// interrupt handler overhead with the cycle counter `C`.
+        ///
+        /// The overhead is logged for every interrupt and recorded per [`InterruptKind`] in the
+        /// `profile()` of the backend, along with its breakdown by number of released waiters.
+        /// Once the sample limit is reached, the statistics are reported before taking its
+        /// [`LimitAction`](crate::profiler::LimitAction).
+        #[cfg(feature = "profiling")]
+        pub struct $profiler_name<C>(PhantomData<C>);
+
+        #[cfg(feature = "profiling")]
+        impl<C: CycleSource> MonotonicProfiler for $profiler_name<C> {
+            fn on_interrupt_enter(_kind: InterruptKind) {
+                $profile.set_entry_cycles(C::cycles());
+            }
+
+            fn on_interrupt_exit(kind: InterruptKind) {
+                let overhead = cycles_to_ns::<C>(C::cycles().wrapping_sub($profile.entry_cycles()));
+                $profile.set_handler_overhead(overhead);
+                $profile.record_interrupt(kind, overhead);
+                defmt::info!("{} {} interrupt overhead: {} ns", stringify!($timer), kind, overhead);
+                defmt::info!("----------------------------------");
+            }
+
+            fn on_wake(released: u32) {
+                if $profile.record_wake(released) {
+                    $backend_name::half_period_monitor().snapshot().report(stringify!($timer));
+                    $profile.end_window(stringify!($timer));
+                }
+            }
+        }
 
         impl $backend_name {
+            /// Timer count extended by the half periods, without accounting the read for the
+            /// profiling.
+            fn ticks() -> u64 {
+                calculate_now(
+                    || $overflow.load(Ordering::Relaxed),
+                    || $timer.cnt().read().cnt()
+                )
+            }
+
             /// Starts the timer.
             ///
             /// **Do not use this function directly.**
@@ -283,18 +441,74 @@ macro_rules! make_timer {
             }
         }
 
+        #[cfg(feature = "profiling")]
+        impl $backend_name {
+            /// Classifies the pending timer interrupt.
+            ///
+            /// **Do not use this function directly.**
+            ///
+            /// Used by the profiled interrupt handler of the prelude macros.
+            pub fn _pending_interrupt_kind() -> InterruptKind {
+                let sr = $timer.sr().read();
+                let compare = sr.ccif(1) && $timer.dier().read().ccie(1);
+                if (sr.uif() || sr.ccif(0)) && !compare {
+                    InterruptKind::HalfPeriod
+                } else {
+                    InterruptKind::Compare
+                }
+            }
+
+            /// Sets the [`MonotonicProfiler::on_compare_set`] hook.
+            ///
+            /// **Do not use this function directly.**
+            ///
+            /// Use the prelude macros instead.
+            ///
+            /// # Safety
+            ///
+            /// Must be called before `_start`.
+            pub unsafe fn _set_compare_hook(hook: fn(u64)) {
+                Self::_compare_hook().set(hook);
+            }
+
+            fn _compare_hook() -> &'static CompareHook {
+                static HOOK: CompareHook = CompareHook::new();
+                &HOOK
+            }
+
+            /// Counter of the waiters released by the timer queue.
+            ///
+            /// **Do not use this function directly.**
+            ///
+            /// Used by the profiled interrupt handler of the prelude macros.
+            pub fn _release_counter() -> &'static ReleaseCounter {
+                static RELEASES: ReleaseCounter = ReleaseCounter::new();
+                &RELEASES
+            }
+
+            /// Profiling state filled by the reference profiler of this timer.
+            pub fn profile() -> &'static ProfileState {
+                &$profile
+            }
+
+            /// Late and missed half-period interrupts of this timer.
+            pub fn half_period_monitor() -> &'static HalfPeriodMonitor {
+                static MONITOR: HalfPeriodMonitor = HalfPeriodMonitor::new();
+                &MONITOR
+            }
+        }
+
         impl TimerQueueBackend for $backend_name {
             type Ticks = u64;
 
             fn now() -> Self::Ticks {
-                calculate_now(
-                    || $overflow.load(Ordering::Relaxed),
-                    || $timer.cnt().read().cnt()
-                )
+                #[cfg(feature = "profiling")]
+                Self::_release_counter().on_now();
+                Self::ticks()
             }
 
             fn set_compare(instant: Self::Ticks) {
-                let now = Self::now();
+                let now = Self::ticks();
 
                 // Since the timer may or may not overflow based on the requested compare val, we check how many ticks are left.
                 // `wrapping_sub` takes care of the u64 integer overflow special case.
@@ -306,6 +520,8 @@ macro_rules! make_timer {
                 };
 
                 $timer.ccr(1).write(|r| r.set_ccr(val.into()));
+                #[cfg(feature = "profiling")]
+                Self::_release_counter().on_compare(|| Self::_compare_hook().call(instant));
             }
 
             fn clear_compare_flag() {
@@ -327,6 +543,7 @@ macro_rules! make_timer {
                 $timer.dier().modify(|r| r.set_ccie(1, false));
             }
 
+            #[cfg(not(feature = "profiling"))]
             fn on_interrupt() {
                 // Full period
                 if $timer.sr().read().uif() {
@@ -348,6 +565,27 @@ macro_rules! make_timer {
                 }
             }
 
+            #[cfg(feature = "profiling")]
+            fn on_interrupt() {
+                let sr = $timer.sr().read();
+                let (overflow, half_period) = (sr.uif(), sr.ccif(0));
+                if overflow || half_period {
+                    $timer.sr().write(|r| {
+                        r.0 = !0;
+                        r.set_uif(!overflow);
+                        r.set_ccif(0, !half_period);
+                    });
+                }
+                Self::half_period_monitor()._on_interrupt(
+                    $overflow.load(Ordering::Relaxed),
+                    overflow,
+                    half_period,
+                    1 << ($bits::BITS - 1),
+                    |added| { $overflow.fetch_add(added as u64, Ordering::Relaxed); },
+                    Self::ticks,
+                );
+            }
+
             fn timer_queue() -> &'static TimerQueue<$backend_name> {
                 &$tq
             }
@@ -356,16 +594,16 @@ macro_rules! make_timer {
 }
 
 #[cfg(feature = "stm32_tim2")]
-make_timer!(Tim2Backend, TIM2, u32, TIMER2_OVERFLOWS, TIMER2_TQ);
+make_timer!(Tim2Backend, Tim2Profiler, TIM2, u32, TIMER2_OVERFLOWS, TIMER2_TQ, TIMER2_PROFILE);
 
 #[cfg(feature = "stm32_tim3")]
-make_timer!(Tim3Backend, TIM3, u16, TIMER3_OVERFLOWS, TIMER3_TQ);
+make_timer!(Tim3Backend, Tim3Profiler, TIM3, u16, TIMER3_OVERFLOWS, TIMER3_TQ, TIMER3_PROFILE);
 
 #[cfg(feature = "stm32_tim4")]
-make_timer!(Tim4Backend, TIM4, u16, TIMER4_OVERFLOWS, TIMER4_TQ);
+make_timer!(Tim4Backend, Tim4Profiler, TIM4, u16, TIMER4_OVERFLOWS, TIMER4_TQ, TIMER4_PROFILE);
 
 #[cfg(feature = "stm32_tim5")]
-make_timer!(Tim5Backend, TIM5, u16, TIMER5_OVERFLOWS, TIMER5_TQ);
+make_timer!(Tim5Backend, Tim5Profiler, TIM5, u16, TIMER5_OVERFLOWS, TIMER5_TQ, TIMER5_PROFILE);
 
 #[cfg(feature = "stm32_tim15")]
-make_timer!(Tim15Backend, TIM15, u16, TIMER15_OVERFLOWS, TIMER15_TQ);
+make_timer!(Tim15Backend, Tim15Profiler, TIM15, u16, TIMER15_OVERFLOWS, TIMER15_TQ, TIMER15_PROFILE);
@@ -28,6 +28,48 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Fine timestamps
+//!
+//! `now()` has the resolution of a tick. The `now_fine()` function of the monotonic also accounts
+//! for the SysTick current value, giving timestamps with the resolution of the core clock, e.g.
+//! for defmt timestamps or lateness measurements:
+//!
+//! ```
+//! # use rtic_monotonics::systick::prelude::*;
+//! # systick_monotonic!(Mono, 1_000);
+//! defmt::timestamp!("{=u64:us}", Mono::now_fine().duration_since_epoch().to_micros());
+//! ```
+//!
+//! # Profiling
+//!
+//! With the `profiling` feature, a [profiler](crate::profiler) can be given as the last argument
+//! of the macro.
+//! `OverheadProfiler` measures the overhead of the SysTick interrupt as the deviation of its
+//! period from the nominal tick period, and reports it over defmt:
+//!
+//! ```
+//! use rtic_monotonics::profiler::CycleSource;
+//! use rtic_monotonics::systick::{prelude::*, OverheadProfiler};
+//!
+//! struct Dwt;
+//!
+//! impl CycleSource for Dwt {
+//!     fn cycles() -> u32 {
+//!         cortex_m::peripheral::DWT::cycle_count()
+//!     }
+//!
+//!     fn rate_mhz() -> u32 {
+//!         12
+//!     }
+//! }
+//!
+//! systick_monotonic!(Mono, 1_000, profiler = OverheadProfiler<Dwt>);
+//! ```
+//!
+//! The profiled monotonic supports both tick counter widths. The 64-bit counter of the
+//! `systick-64bit` feature does not wrap in practice, but falls back to a critical section on
+//! every `now()` and tick on targets without 64-bit atomics.
 
 /// Common definitions and traits for using the systick monotonic
 pub mod prelude {
@@ -53,10 +95,15 @@ use rtic_time::timer_queue::TimerQueue;
 use defmt_rtt as _;
 #[cfg(feature = "semihosting")]
 use defmt_semihosting as _;
-#[cfg(not(any(feature = "rtt", feature = "semihosting")))]
-compile_error!("No global logger selected, enable either the rtt or semihosting feature");
 
+#[cfg(feature = "profiling")]
+use crate::profiler::{
+    cycles_to_ns, CompareHook, CycleSource, InterruptKind, MonotonicProfiler, ProfileState,
+    ReleaseCounter,
+};
 use crate::TimerQueueBackend;
+#[cfg(feature = "profiling")]
+use core::marker::PhantomData;
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "systick-64bit")] {
@@ -69,6 +116,16 @@ cfg_if::cfg_if! {
 }
 
 static SYSTICK_TIMER_QUEUE: TimerQueue<SystickBackend> = TimerQueue::new();
+/// SysTick clock and tick rate, to compute the fine timestamps
+static SYSTICK_SYSCLK: portable_atomic::AtomicU32 = portable_atomic::AtomicU32::new(0);
+static SYSTICK_TIMER_HZ: portable_atomic::AtomicU32 = portable_atomic::AtomicU32::new(0);
+#[cfg(feature = "profiling")]
+static SYSTICK_COMPARE_HOOK: CompareHook = CompareHook::new();
+#[cfg(feature = "profiling")]
+static SYSTICK_RELEASES: ReleaseCounter = ReleaseCounter::new();
+
+/// Instant of [`SystickBackend::now_fine`], in nanoseconds since the start of the monotonic.
+pub type FineInstant = fugit::Instant<u64, 1, 1_000_000_000>;
 
 /// Systick based [`TimerQueueBackend`].
 pub struct SystickBackend;
@@ -93,11 +150,88 @@ impl SystickBackend {
         systick.set_clock_source(cortex_m::peripheral::syst::SystClkSource::Core);
         systick.set_reload(reload);
         systick.enable_interrupt();
+
+        SYSTICK_TIMER_HZ.store(timer_hz, Ordering::Relaxed);
+        SYSTICK_SYSCLK.store(sysclk, Ordering::Release);
+
         systick.enable_counter();
 
         SYSTICK_TIMER_QUEUE.initialize(SystickBackend {});
     }
 
+    /// Current time, with the resolution of the core clock instead of the tick.
+    ///
+    /// Combines the tick count with the time elapsed in the current tick, given by the SysTick
+    /// current value. Returns the epoch until the monotonic is started.
+    pub fn now_fine() -> FineInstant {
+        let sysclk = SYSTICK_SYSCLK.load(Ordering::Acquire);
+        if sysclk == 0 {
+            return FineInstant::from_ticks(0);
+        }
+        let timer_hz = SYSTICK_TIMER_HZ.load(Ordering::Relaxed) as u64;
+        let reload = SYST::get_reload();
+
+        let (ticks, current) = loop {
+            let ticks = Self::ticks();
+            let current = SYST::get_current();
+            // SysTick wrapped after reading the tick count, which is then one tick behind the
+            // current value: account for the wrap and read again.
+            if Self::systick().has_wrapped() {
+                SYSTICK_CNT.fetch_add(1, Ordering::AcqRel);
+                continue;
+            }
+            // The SysTick interrupt accounted for a wrap in between.
+            if SYSTICK_CNT.load(Ordering::Relaxed) != ticks {
+                continue;
+            }
+            break (u64::from(ticks), current);
+        };
+
+        let tick_ns = ticks * 1_000_000_000 / timer_hz;
+        // SysTick counts down from the reload value
+        let elapsed_ns = (reload - current) as u64 * 1_000_000_000 / sysclk as u64;
+        FineInstant::from_ticks(tick_ns + elapsed_ns)
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Profiling state filled by [`OverheadProfiler`].
+    pub fn profile() -> &'static ProfileState {
+        &SYSTICK_PROFILE
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Sets the [`MonotonicProfiler::on_compare_set`] hook.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Use the prelude macros instead.
+    ///
+    /// # Safety
+    ///
+    /// Must be called before [`SystickBackend::_start`].
+    pub unsafe fn _set_compare_hook(hook: fn(u64)) {
+        SYSTICK_COMPARE_HOOK.set(hook);
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Counter of the waiters released by the timer queue.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Use the prelude macros instead.
+    pub fn _release_counter() -> &'static ReleaseCounter {
+        &SYSTICK_RELEASES
+    }
+
+    /// Tick count, without accounting the read for the profiling.
+    fn ticks() -> <Self as TimerQueueBackend>::Ticks {
+        if Self::systick().has_wrapped() {
+            SYSTICK_CNT.fetch_add(1, Ordering::AcqRel);
+        }
+
+        SYSTICK_CNT.load(Ordering::Relaxed)
+    }
+
     fn systick() -> SYST {
         unsafe { core::mem::transmute::<(), SYST>(()) }
     }
@@ -113,15 +247,16 @@ impl TimerQueueBackend for SystickBackend {
     }
 
     fn now() -> Self::Ticks {
-        if Self::systick().has_wrapped() {
-            SYSTICK_CNT.fetch_add(1, Ordering::AcqRel);
-        }
-
-        SYSTICK_CNT.load(Ordering::Relaxed)
+        #[cfg(feature = "profiling")]
+        SYSTICK_RELEASES.on_now();
+        Self::ticks()
     }
 
-    fn set_compare(_: Self::Ticks) {
-        // No need to do something here, we get interrupts anyway.
+    #[allow(unused_variables)]
+    fn set_compare(instant: Self::Ticks) {
+        // No need to program anything here, we get interrupts anyway.
+        #[cfg(feature = "profiling")]
+        SYSTICK_RELEASES.on_compare(|| SYSTICK_COMPARE_HOOK.call(instant.into()));
     }
 
     fn clear_compare_flag() {
@@ -143,14 +278,49 @@ impl TimerQueueBackend for SystickBackend {
     }
 }
 
-/// Previous measured time in ns (to compute overhead)
-pub static mut PREVIOUS_TIME: f32 = 0.0;
-/// Threshold for WCET measurements
-pub static WCET_THRESHOLD: u32 = 500; 
-/// Measurement counter
-pub static mut MEASUREMENT_COUNTER: u32 = 0;
-/// Worst-case overhead in ns
-pub static mut WC_OVERHEAD: f32 = 0.0;
+/// Profiling state of the SysTick monotonic.
+#[cfg(feature = "profiling")]
+static SYSTICK_PROFILE: ProfileState = ProfileState::new();
+
+/// Reference [`MonotonicProfiler`] of the SysTick monotonic, timestamping the interrupts with
+/// the cycle counter `C`.
+///
+/// The overhead is the time elapsed since the previous interrupt, minus the tick period.
+/// It is logged for every interrupt and recorded as the [`InterruptKind::Tick`] overhead of
+/// [`SystickBackend::profile`]. The handler execution time, from entry to exit, is broken down by
+/// number of released waiters. Once the sample limit is reached, the statistics are reported
+/// before taking its [`LimitAction`](crate::profiler::LimitAction).
+#[cfg(feature = "profiling")]
+pub struct OverheadProfiler<C>(PhantomData<C>);
+
+#[cfg(feature = "profiling")]
+impl<C: CycleSource> MonotonicProfiler for OverheadProfiler<C> {
+    fn on_interrupt_enter(_kind: InterruptKind) {
+        SYSTICK_PROFILE.set_entry_cycles(C::cycles());
+    }
+
+    fn on_interrupt_exit(kind: InterruptKind) {
+        let cycles = C::cycles();
+        let profile = &SYSTICK_PROFILE;
+        profile.set_handler_overhead(cycles_to_ns::<C>(cycles.wrapping_sub(profile.entry_cycles())));
+        // The first interrupt only sets the reference
+        let Some(previous) = profile.replace_previous_cycles(cycles) else {
+            return;
+        };
+        let period_ns = 1_000_000_000 / SYSTICK_TIMER_HZ.load(Ordering::Relaxed);
+        let overhead = cycles_to_ns::<C>(cycles.wrapping_sub(previous)) // elapsed time since previous-time (ns)
+            .saturating_sub(period_ns);                                  // - timer period (ns) = overhead (ns)
+        profile.record_interrupt(kind, overhead);
+        defmt::info!("SysTick overhead: {} ns", overhead);
+        defmt::info!("----------------------------------");
+    }
+
+    fn on_wake(released: u32) {
+        if SYSTICK_PROFILE.record_wake(released) {
+            SYSTICK_PROFILE.end_window("SysTick");
+        }
+    }
+}
 
 /// Create a Systick based monotonic and register the Systick interrupt for it.
 ///
@@ -170,12 +340,71 @@ pub static mut WC_OVERHEAD: f32 = 0.0;
 /// * `name` - The name that the monotonic type will have.
 /// * `tick_rate_hz` - The tick rate of the timer peripheral.
 ///   Can be omitted; defaults to 1kHz.
+/// * `profiler = P` - The [`MonotonicProfiler`](crate::profiler::MonotonicProfiler) of the
+///   monotonic. Requires the `profiling` feature; see [profiling](crate::systick#profiling).
 #[macro_export]
 macro_rules! systick_monotonic {
     ($name:ident) => {
         $crate::systick_monotonic!($name, 1_000);
     };
-    ($name:ident, $tick_rate_hz:expr, $core_rate_mhz:expr, $dwt_ref:expr) => {
+    ($name:ident, $tick_rate_hz:expr) => {
+        $crate::__internal_create_systick_struct!($name, $tick_rate_hz, {
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            unsafe extern "C" fn SysTick() {
+                use $crate::TimerQueueBackend;
+                $crate::systick::SystickBackend::timer_queue().on_monotonic_interrupt();
+            }
+        });
+    };
+    ($name:ident, $tick_rate_hz:expr, profiler = $profiler:ty) => {
+        $crate::__internal_create_profiled_systick_struct!($name, $tick_rate_hz, $profiler);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(feature = "profiling")]
+#[macro_export]
+macro_rules! __internal_create_profiled_systick_struct {
+    ($name:ident, $tick_rate_hz:expr, $profiler:ty) => {
+        $crate::__internal_create_systick_struct!($name, $tick_rate_hz, {
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            unsafe extern "C" fn SysTick() {
+                use $crate::profiler::{InterruptKind, MonotonicProfiler};
+                use $crate::TimerQueueBackend;
+                <$profiler as MonotonicProfiler>::on_interrupt_enter(InterruptKind::Tick);
+                let released = $crate::systick::SystickBackend::_release_counter()
+                    .count(|| $crate::systick::SystickBackend::timer_queue().on_monotonic_interrupt());
+                <$profiler as MonotonicProfiler>::on_interrupt_exit(InterruptKind::Tick);
+                <$profiler as MonotonicProfiler>::on_wake(released);
+            }
+
+            // SAFETY: the timer queue is not initialized yet.
+            unsafe {
+                $crate::systick::SystickBackend::_set_compare_hook(
+                    <$profiler as $crate::profiler::MonotonicProfiler>::on_compare_set,
+                )
+            };
+        });
+
+        $crate::__internal_create_profile_access!($name, $crate::systick::SystickBackend);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "profiling"))]
+#[macro_export]
+macro_rules! __internal_create_profiled_systick_struct {
+    ($($tt:tt)*) => {
+        compile_error!("A profiler requires the `profiling` feature of profiled-rtic-monotonics");
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __internal_create_systick_struct {
+    ($name:ident, $tick_rate_hz:expr, { $($setup:tt)* }) => {
         /// A `Monotonic` based on SysTick.
         pub struct $name;
 
@@ -190,42 +419,15 @@ macro_rules! systick_monotonic {
             ///
             /// This method must be called only once.
             pub fn start(systick: $crate::systick::SYST, sysclk: u32) {
-                use profiled_rtic_monotonics::systick::{
-                    PREVIOUS_TIME,
-                    MEASUREMENT_COUNTER,
-                    WC_OVERHEAD,
-                    WCET_THRESHOLD,
-                };
-
-                #[no_mangle]
-                #[allow(non_snake_case)]
-                unsafe extern "C" fn SysTick() {
-                    use $crate::TimerQueueBackend;
-                    $crate::systick::SystickBackend::timer_queue().on_monotonic_interrupt();
-                    let cycles = unsafe { (*$dwt_ref).cyccnt.read() };
-                    let overhead = 
-                        (cycles as f32 / $core_rate_mhz) * 1_000.0 // all-time (ns)
-                        - PREVIOUS_TIME                            // - previous-time (ns) 
-                        - 1_000_000.0;                             // - 1ms timer period (ns) = overhead (ns)
-                    // update previous_time
-                    PREVIOUS_TIME = (cycles as f32 / $core_rate_mhz) * 1_000.0;
-                    // update worst-case overhead
-                    if overhead > WC_OVERHEAD {
-                        WC_OVERHEAD = overhead;
-                    }
-                    defmt::info!("SysTick overhead: {} ns", overhead as u32);
-                    defmt::info!("----------------------------------");
-                    // update counter
-                    MEASUREMENT_COUNTER += 1;
-                    // print worst-case overhead every WCET_THRESHOLD measurements
-                    if MEASUREMENT_COUNTER >= WCET_THRESHOLD {
-                        defmt::info!("Worst-case SysTick overhead: {} ns", WC_OVERHEAD as u32);
-                        defmt::panic!("End of systick measurements, stopping.");
-                    }
-                }
+                $($setup)*
 
                 $crate::systick::SystickBackend::_start(systick, sysclk, $tick_rate_hz);
-                unsafe { (*$dwt_ref).cyccnt.write(0) };
+            }
+
+            /// Current time, with the resolution of the core clock, see
+            /// [`SystickBackend::now_fine`]($crate::systick::SystickBackend::now_fine).
+            pub fn now_fine() -> $crate::systick::FineInstant {
+                $crate::systick::SystickBackend::now_fine()
             }
         }
 
@@ -0,0 +1,423 @@
+//! Tickless [`Monotonic`](rtic_time::Monotonic) based on Cortex-M SysTick.
+//!
+//! Unlike [`crate::systick`], which interrupts at the tick rate, this implementation reprograms
+//! the SysTick reload value to the next deadline of the timer queue. Deadlines further than the
+//! 24-bit range of SysTick are reached through intermediate interrupts, and the timer is left
+//! running at its full range when the queue is empty, to keep track of time.
+//!
+//! Time is counted in core clock cycles, from the length of the elapsed SysTick periods, and only
+//! converted into ticks when read. SysTick keeps running when the queue is empty, and only the
+//! period programmed for the next deadline is cut short: all the periods after it last the full
+//! 24-bit range, so that the interrupt handler can be delayed by up to 2^24 cycles without losing
+//! a period. Restarting the counter for a new deadline loses the cycles between reading it and
+//! clearing it, which are compensated with their count measured when the monotonic starts: the
+//! time base does not drift with the number of reprogrammings.
+//!
+//! # Example
+//!
+//! ```
+//! use rtic_monotonics::systick_tickless::prelude::*;
+//!
+//! // Create the type `Mono`, with a resolution of 1 ms.
+//! systick_tickless_monotonic!(Mono, 1_000);
+//!
+//! fn init() {
+//!     let core_peripherals = cortex_m::Peripherals::take().unwrap();
+//!     // Start the monotonic using the cortex-m crate's Systick driver.
+//!     // We tell it we have a system clock of 12 MHz.
+//!     Mono::start(core_peripherals.SYST, 12_000_000);
+//! }
+//!
+//! async fn usage() {
+//!     loop {
+//!          // You can use the monotonic to get the time...
+//!          let timestamp = Mono::now();
+//!          // ...and you can use it to add a delay to this async function
+//!          Mono::delay(100.millis()).await;
+//!     }
+//! }
+//! ```
+//!
+//! # Profiling
+//!
+//! With the `profiling` feature, the macro accepts a [profiler](crate::profiler), called with
+//! `InterruptKind::Compare` interrupts. `HandlerProfiler` measures the execution time of the
+//! interrupt handler, to compare it with the one of the ticking SysTick monotonic.
+
+/// Common definitions and traits for using the tickless systick monotonic
+pub mod prelude {
+    pub use crate::systick_tickless_monotonic;
+
+    pub use crate::Monotonic;
+
+    pub use fugit::{self, ExtU64, ExtU64Ceil};
+}
+
+pub use cortex_m::peripheral::SYST;
+
+#[cfg(feature = "profiling")]
+use crate::profiler::{
+    cycles_to_ns, CompareHook, CycleSource, InterruptKind, MonotonicProfiler, ProfileState,
+    ReleaseCounter,
+};
+use crate::TimerQueueBackend;
+use core::cell::Cell;
+#[cfg(feature = "profiling")]
+use core::marker::PhantomData;
+use cortex_m::interrupt::{self, CriticalSection, Mutex};
+use rtic_time::timer_queue::TimerQueue;
+
+/// Longest SysTick period, in cycles (24-bit reload value).
+const MAX_CYCLES: u32 = 0x0100_0000;
+/// Cycles left in the current period below which it is not restarted before it reloads, so that
+/// it does not reload while being restarted.
+const RESTART_MARGIN: u32 = 256;
+
+static SYSTICK_TIMER_QUEUE: TimerQueue<SystickTicklessBackend> = TimerQueue::new();
+#[cfg(feature = "profiling")]
+static SYSTICK_COMPARE_HOOK: CompareHook = CompareHook::new();
+#[cfg(feature = "profiling")]
+static SYSTICK_RELEASES: ReleaseCounter = ReleaseCounter::new();
+
+/// Cycles counted up to the start of the current SysTick period
+static PERIOD_START: Mutex<Cell<u64>> = Mutex::new(Cell::new(0));
+/// Length of the current SysTick period, in cycles. The next ones last `MAX_CYCLES`.
+static PERIOD: Mutex<Cell<u32>> = Mutex::new(Cell::new(MAX_CYCLES));
+/// Cycles from reading the counter to the start of the period restarted by clearing it
+static RESTART_CYCLES: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+/// Cycles per tick of the monotonic
+static CYCLES_PER_TICK: Mutex<Cell<u32>> = Mutex::new(Cell::new(1));
+/// Shortest period to program
+static MIN_DELAY: Mutex<Cell<u32>> = Mutex::new(Cell::new(MAX_CYCLES));
+
+/// Tickless Systick based [`TimerQueueBackend`].
+pub struct SystickTicklessBackend;
+
+impl SystickTicklessBackend {
+    /// Starts the monotonic timer.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Use the prelude macros instead.
+    pub fn _start(mut systick: SYST, sysclk: u32, timer_hz: u32) {
+        assert!(
+            (sysclk % timer_hz) == 0,
+            "timer_hz cannot evenly divide sysclk! Please adjust the timer or sysclk frequency."
+        );
+        let cycles_per_tick = sysclk / timer_hz;
+
+        assert!(cycles_per_tick <= MAX_CYCLES);
+        assert!(cycles_per_tick > 2 * RESTART_MARGIN);
+
+        systick.disable_counter();
+        systick.set_clock_source(cortex_m::peripheral::syst::SystClkSource::Core);
+        systick.set_reload(MAX_CYCLES - 1);
+        systick.clear_current();
+        systick.enable_interrupt();
+        systick.enable_counter();
+
+        interrupt::free(|cs| {
+            PERIOD_START.borrow(cs).set(0);
+            PERIOD.borrow(cs).set(MAX_CYCLES);
+            CYCLES_PER_TICK.borrow(cs).set(cycles_per_tick);
+            // As Zephyr: at least 1024 cycles or 1/16 tick
+            MIN_DELAY
+                .borrow(cs)
+                .set((cycles_per_tick / 16).max(1024).min(cycles_per_tick));
+
+            // Clearing the counter right after reading it takes as long as a second read, then
+            // the cleared counter reloads on the next cycle. The first period is far from its end.
+            let val1 = SYST::get_current();
+            let val2 = SYST::get_current();
+            RESTART_CYCLES.borrow(cs).set(val1 - val2 + 1);
+        });
+
+        SYSTICK_TIMER_QUEUE.initialize(SystickTicklessBackend {});
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Profiling state filled by [`HandlerProfiler`].
+    pub fn profile() -> &'static ProfileState {
+        &SYSTICK_PROFILE
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Sets the [`MonotonicProfiler::on_compare_set`] hook.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Use the prelude macros instead.
+    ///
+    /// # Safety
+    ///
+    /// Must be called before [`SystickTicklessBackend::_start`].
+    pub unsafe fn _set_compare_hook(hook: fn(u64)) {
+        SYSTICK_COMPARE_HOOK.set(hook);
+    }
+
+    #[cfg(feature = "profiling")]
+    /// Counter of the waiters released by the timer queue.
+    ///
+    /// **Do not use this function directly.**
+    ///
+    /// Use the prelude macros instead.
+    pub fn _release_counter() -> &'static ReleaseCounter {
+        &SYSTICK_RELEASES
+    }
+
+    fn systick() -> SYST {
+        unsafe { core::mem::transmute::<(), SYST>(()) }
+    }
+
+    /// Cycles counted since the monotonic started, accumulating the current period if it ended.
+    ///
+    /// A single end of period can be noticed, hence the interrupts must not be masked for a
+    /// whole `MAX_CYCLES` period, the length of all the periods after the programmed one.
+    fn cycles(cs: &CriticalSection) -> u64 {
+        let period_start = PERIOD_START.borrow(cs);
+        let period = PERIOD.borrow(cs);
+        let val1 = SYST::get_current();
+        // Reading COUNTFLAG clears it
+        let wrapped = Self::systick().has_wrapped();
+        let val2 = SYST::get_current();
+        if wrapped || val1 < val2 {
+            period_start.set(period_start.get() + period.get() as u64);
+            period.set(MAX_CYCLES);
+            if !wrapped {
+                // The wrap happened after reading COUNTFLAG, clear it
+                let _ = Self::systick().has_wrapped();
+            }
+        }
+        period_start.get() + (period.get() - 1 - val2) as u64
+    }
+
+    /// Restarts the counter with a period of `delay` cycles, the next ones lasting `MAX_CYCLES`.
+    fn reprogram(cs: &CriticalSection, delay: u32) {
+        let delay = delay.clamp(MIN_DELAY.borrow(cs).get(), MAX_CYCLES);
+        let mut systick = Self::systick();
+
+        // Let the current period reload first if it is about to
+        while SYST::get_current() < RESTART_MARGIN {}
+        Self::cycles(cs);
+
+        systick.set_reload(delay - 1);
+        let val = SYST::get_current();
+        // Also clears COUNTFLAG, the counter restarts from the new reload value
+        systick.clear_current();
+
+        let period_start = PERIOD_START.borrow(cs);
+        let period = PERIOD.borrow(cs);
+        period_start.set(
+            period_start.get()
+                + (period.get() - 1 - val) as u64
+                + RESTART_CYCLES.borrow(cs).get() as u64,
+        );
+        period.set(delay);
+
+        // Once reloaded, the reload value only applies to the next periods
+        while SYST::get_current() == 0 {}
+        systick.set_reload(MAX_CYCLES - 1);
+    }
+}
+
+impl TimerQueueBackend for SystickTicklessBackend {
+    type Ticks = u64;
+
+    fn now() -> Self::Ticks {
+        #[cfg(feature = "profiling")]
+        SYSTICK_RELEASES.on_now();
+        interrupt::free(|cs| Self::cycles(cs) / CYCLES_PER_TICK.borrow(cs).get() as u64)
+    }
+
+    fn set_compare(instant: Self::Ticks) {
+        interrupt::free(|cs| {
+            let now = Self::cycles(cs);
+            let deadline = instant.saturating_mul(CYCLES_PER_TICK.borrow(cs).get() as u64);
+            let delay = deadline.saturating_sub(now).min(MAX_CYCLES as u64) as u32;
+            Self::reprogram(cs, delay);
+        });
+        #[cfg(feature = "profiling")]
+        SYSTICK_RELEASES.on_compare(|| SYSTICK_COMPARE_HOOK.call(instant));
+    }
+
+    fn clear_compare_flag() {
+        // NOOP with SysTick interrupt
+    }
+
+    fn pend_interrupt() {
+        cortex_m::peripheral::SCB::set_pendst();
+    }
+
+    fn on_interrupt() {
+        // Accumulate the period that just ended
+        interrupt::free(|cs| {
+            Self::cycles(cs);
+        });
+    }
+
+    fn disable_timer() {
+        // Keep counting time: the periods after the programmed one already last `MAX_CYCLES`
+    }
+
+    fn timer_queue() -> &'static TimerQueue<Self> {
+        &SYSTICK_TIMER_QUEUE
+    }
+}
+
+/// Profiling state of the tickless SysTick monotonic.
+#[cfg(feature = "profiling")]
+static SYSTICK_PROFILE: ProfileState = ProfileState::new();
+
+/// Reference [`MonotonicProfiler`] of the tickless SysTick monotonic, measuring the execution
+/// time of the interrupt handler, from entry to exit, with the cycle counter `C`.
+///
+/// The execution time is logged for every interrupt and recorded as the
+/// [`InterruptKind::Compare`] overhead of [`SystickTicklessBackend::profile`], along with its
+/// breakdown by number of released waiters. Once the sample limit is reached, the statistics are
+/// reported before taking its [`LimitAction`](crate::profiler::LimitAction).
+#[cfg(feature = "profiling")]
+pub struct HandlerProfiler<C>(PhantomData<C>);
+
+#[cfg(feature = "profiling")]
+impl<C: CycleSource> MonotonicProfiler for HandlerProfiler<C> {
+    fn on_interrupt_enter(_kind: InterruptKind) {
+        SYSTICK_PROFILE.set_entry_cycles(C::cycles());
+    }
+
+    fn on_interrupt_exit(kind: InterruptKind) {
+        let overhead = cycles_to_ns::<C>(C::cycles().wrapping_sub(SYSTICK_PROFILE.entry_cycles()));
+        SYSTICK_PROFILE.set_handler_overhead(overhead);
+        SYSTICK_PROFILE.record_interrupt(kind, overhead);
+        defmt::info!("Tickless SysTick handler: {} ns", overhead);
+        defmt::info!("----------------------------------");
+    }
+
+    fn on_wake(released: u32) {
+        if SYSTICK_PROFILE.record_wake(released) {
+            SYSTICK_PROFILE.end_window("Tickless SysTick");
+        }
+    }
+}
+
+/// Create a tickless Systick based monotonic and register the Systick interrupt for it.
+///
+/// This macro expands to produce a new type called `$name`, which has a `fn
+/// start()` function for you to call. The type has an implementation of the
+/// `rtic_monotonics::TimerQueueBasedMonotonic` trait, the
+/// `embedded_hal::delay::DelayNs` trait and the
+/// `embedded_hal_async::delay::DelayNs` trait.
+///
+/// This macro also produces an interrupt handler for the SysTick interrupt, by
+/// creating an `extern "C" fn SysTick() { ... }`: it cannot be used together with
+/// [`systick_monotonic!`](crate::systick_monotonic).
+///
+/// See [`crate::systick_tickless`] for more details.
+///
+/// # Arguments
+///
+/// * `name` - The name that the monotonic type will have.
+/// * `tick_rate_hz` - The resolution of the monotonic.
+///   Can be omitted; defaults to 1kHz.
+/// * `profiler = P` - The [`MonotonicProfiler`](crate::profiler::MonotonicProfiler) of the
+///   monotonic. Requires the `profiling` feature; see [profiling](crate::systick_tickless#profiling).
+#[macro_export]
+macro_rules! systick_tickless_monotonic {
+    ($name:ident) => {
+        $crate::systick_tickless_monotonic!($name, 1_000);
+    };
+    ($name:ident, $tick_rate_hz:expr) => {
+        $crate::__internal_create_systick_tickless_struct!($name, $tick_rate_hz, {
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            unsafe extern "C" fn SysTick() {
+                use $crate::TimerQueueBackend;
+                $crate::systick_tickless::SystickTicklessBackend::timer_queue().on_monotonic_interrupt();
+            }
+        });
+    };
+    ($name:ident, $tick_rate_hz:expr, profiler = $profiler:ty) => {
+        $crate::__internal_create_profiled_systick_tickless_struct!($name, $tick_rate_hz, $profiler);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(feature = "profiling")]
+#[macro_export]
+macro_rules! __internal_create_profiled_systick_tickless_struct {
+    ($name:ident, $tick_rate_hz:expr, $profiler:ty) => {
+        $crate::__internal_create_systick_tickless_struct!($name, $tick_rate_hz, {
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            unsafe extern "C" fn SysTick() {
+                use $crate::profiler::{InterruptKind, MonotonicProfiler};
+                use $crate::TimerQueueBackend;
+                <$profiler as MonotonicProfiler>::on_interrupt_enter(InterruptKind::Compare);
+                let released = $crate::systick_tickless::SystickTicklessBackend::_release_counter()
+                    .count(|| $crate::systick_tickless::SystickTicklessBackend::timer_queue().on_monotonic_interrupt());
+                <$profiler as MonotonicProfiler>::on_interrupt_exit(InterruptKind::Compare);
+                <$profiler as MonotonicProfiler>::on_wake(released);
+            }
+
+            // SAFETY: the timer queue is not initialized yet.
+            unsafe {
+                $crate::systick_tickless::SystickTicklessBackend::_set_compare_hook(
+                    <$profiler as $crate::profiler::MonotonicProfiler>::on_compare_set,
+                )
+            };
+        });
+
+        $crate::__internal_create_profile_access!($name, $crate::systick_tickless::SystickTicklessBackend);
+    };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "profiling"))]
+#[macro_export]
+macro_rules! __internal_create_profiled_systick_tickless_struct {
+    ($($tt:tt)*) => {
+        compile_error!("A profiler requires the `profiling` feature of profiled-rtic-monotonics");
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __internal_create_systick_tickless_struct {
+    ($name:ident, $tick_rate_hz:expr, { $($setup:tt)* }) => {
+        /// A tickless `Monotonic` based on SysTick.
+        pub struct $name;
+
+        impl $name {
+            /// Starts the `Monotonic`.
+            ///
+            /// The `sysclk` parameter is the speed at which SysTick runs at. This value should come from
+            /// the clock generation function of the used HAL.
+            ///
+            /// Panics if it is impossible to achieve the desired monotonic tick rate based
+            /// on the given `sysclk` parameter. If that happens, adjust the desired monotonic tick rate.
+            ///
+            /// This method must be called only once.
+            pub fn start(systick: $crate::systick_tickless::SYST, sysclk: u32) {
+                $($setup)*
+
+                $crate::systick_tickless::SystickTicklessBackend::_start(systick, sysclk, $tick_rate_hz);
+            }
+        }
+
+        impl $crate::TimerQueueBasedMonotonic for $name {
+            type Backend = $crate::systick_tickless::SystickTicklessBackend;
+            type Instant = $crate::fugit::Instant<
+                <Self::Backend as $crate::TimerQueueBackend>::Ticks,
+                1,
+                { $tick_rate_hz },
+            >;
+            type Duration = $crate::fugit::Duration<
+                <Self::Backend as $crate::TimerQueueBackend>::Ticks,
+                1,
+                { $tick_rate_hz },
+            >;
+        }
+
+        $crate::rtic_time::impl_embedded_hal_delay_fugit!($name);
+        $crate::rtic_time::impl_embedded_hal_async_delay_fugit!($name);
+    };
+}
@@ -0,0 +1,23 @@
+#!/bin/sh
+# Runs the systick-width scenario for both tick counter widths, which are selected at build time,
+# and merges the reports of the two runs. Each run stops after its first window: do not enable
+# `continuous`.
+#
+# Usage: scripts/systick-width.sh <cargo run arguments>, e.g.
+#   scripts/systick-width.sh --features semihosting
+#   scripts/systick-width.sh --no-default-features --features lm3s6965evb,semihosting --target thumbv7m-none-eabi
+set -e
+
+report=""
+for bits in 32 64; do
+    features="systick-width"
+    if [ "$bits" = 64 ]; then
+        features="$features,systick-64bit"
+    fi
+    echo "Running the $bits-bit build..." >&2
+    log=$(cargo run "$@" --features "$features")
+    report="$report$(printf '%s\n' "$log" | grep -F "SysTick $bits-bit counter:")
+"
+done
+
+printf '%s' "$report"
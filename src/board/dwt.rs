@@ -0,0 +1,60 @@
+//! Cycle counter based on the DWT CYCCNT register (ARMv7-M and ARMv8-M Mainline).
+
+#[cfg(not(feature = "dwt-mono"))]
+use cortex_m::peripheral::{DCB, DWT};
+#[cfg(feature = "dwt-mono")]
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Free-running counter of core clock cycles.
+///
+/// With the `dwt-mono` feature CYCCNT is the time base of the monotonic, which enables it, hence
+/// the cycles are read from `Mono::now()` and never written: `reset()` records a reference
+/// instant instead.
+pub struct CycleCounter {
+    #[cfg(feature = "dwt-mono")]
+    reference: AtomicU32,
+}
+
+impl CycleCounter {
+    pub(super) const fn new() -> Self {
+        Self {
+            #[cfg(feature = "dwt-mono")]
+            reference: AtomicU32::new(0),
+        }
+    }
+
+    /// Enables CYCCNT, owning the peripherals that control it.
+    #[cfg(not(feature = "dwt-mono"))]
+    pub(super) fn start(mut dcb: DCB, mut dwt: DWT) {
+        dcb.enable_trace();
+        dwt.enable_cycle_counter();
+    }
+
+    #[cfg(not(feature = "dwt-mono"))]
+    pub fn reset(&self) {
+        // SAFETY: CYCCNT is only written here
+        unsafe { (*DWT::PTR).cyccnt.write(0) };
+    }
+
+    #[cfg(not(feature = "dwt-mono"))]
+    pub fn read(&self) -> u32 {
+        DWT::cycle_count()
+    }
+
+    #[cfg(feature = "dwt-mono")]
+    pub fn reset(&self) {
+        self.reference.store(Self::now(), Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "dwt-mono")]
+    pub fn read(&self) -> u32 {
+        Self::now().wrapping_sub(self.reference.load(Ordering::Relaxed))
+    }
+
+    /// Current instant of the monotonic, in core clock cycles, truncated to 32 bits.
+    #[cfg(feature = "dwt-mono")]
+    fn now() -> u32 {
+        use rtic_monotonics::Monotonic;
+        crate::time::Mono::now().ticks() as u32
+    }
+}
@@ -0,0 +1,67 @@
+//! TI LM3S6965 evaluation board: Cortex-M3 without FPU (`thumbv7m-none-eabi`).
+//!
+//! Mainly meant to be run on the QEMU `lm3s6965evb` machine.
+
+use super::Clocks;
+use cortex_m::peripheral::{DCB, DWT};
+
+pub use lm3s6965 as pac;
+
+pub const NAME: &str = "lm3s6965evb";
+pub const CORE: &str = "cortex-m3";
+pub const ARCH: &str = "armv7-m";
+
+pub const CORE_CLOCK_HZ: u32 = 12_000_000;
+
+pub const ISR_SWITCH_IRQ: pac::Interrupt = pac::Interrupt::GPIOA;
+
+/// Run mode clock gating register 0, whose bit 3 clocks the watchdog
+const RCGC0: *mut u32 = 0x400F_E100 as *mut u32;
+const WDTLOAD: *mut u32 = 0x4000_0000 as *mut u32;
+const WDTVALUE: *const u32 = 0x4000_0004 as *const u32;
+const WDTCTL: *mut u32 = 0x4000_0008 as *mut u32;
+
+/// Free-running counter of core clock cycles.
+///
+/// QEMU models neither the DWT cycle counter nor reading the general-purpose timers, hence the
+/// watchdog timer is used instead: it counts down at the system clock, which is also the core
+/// clock. It is loaded with the largest value and its reset output is left disabled, so that it
+/// just wraps around. The device crate has no register definitions, they are accessed directly.
+pub struct CycleCounter {
+    _private: (),
+}
+
+impl CycleCounter {
+    pub(super) const fn new() -> Self {
+        Self { _private: () }
+    }
+
+    fn start() {
+        // SAFETY: the watchdog is only used by the cycle counter
+        unsafe {
+            RCGC0.write_volatile(RCGC0.read_volatile() | 1 << 3);
+            WDTLOAD.write_volatile(u32::MAX);
+            // INTEN starts the counter, which cannot be stopped afterwards; RESEN stays clear
+            WDTCTL.write_volatile(1);
+        }
+    }
+
+    pub fn reset(&self) {
+        // Writing the load value restarts the count from it
+        unsafe { WDTLOAD.write_volatile(u32::MAX) };
+    }
+
+    pub fn read(&self) -> u32 {
+        u32::MAX - unsafe { WDTVALUE.read_volatile() }
+    }
+}
+
+pub fn setup(_device: pac::Peripherals, _dcb: DCB, _dwt: DWT) -> Clocks {
+    // The system clock is left at its reset configuration: 12 MHz.
+    CycleCounter::start();
+    super::check_cycle_counter();
+    Clocks {
+        sysclk_hz: CORE_CLOCK_HZ,
+        hclk_mhz: CORE_CLOCK_HZ / 1_000_000,
+    }
+}
@@ -0,0 +1,70 @@
+//! BBC micro:bit v1: nRF51822, Cortex-M0 (`thumbv6m-none-eabi`).
+//!
+//! Mainly meant to be run on the QEMU `microbit` machine. Note that the nRF51 does not implement
+//! the optional SysTick timer, which QEMU models anyway: the SysTick based monotonic only works
+//! under emulation.
+
+use super::Clocks;
+use cortex_m::peripheral::{DCB, DWT};
+
+pub use nrf51_pac as pac;
+
+pub const NAME: &str = "microbit";
+pub const CORE: &str = "cortex-m0";
+pub const ARCH: &str = "armv6-m";
+
+pub const CORE_CLOCK_HZ: u32 = 16_000_000;
+
+pub const ISR_SWITCH_IRQ: pac::Interrupt = pac::Interrupt::SWI2;
+
+/// Free-running counter of core clock cycles.
+///
+/// ARMv6-M has no DWT CYCCNT, hence TIMER0 is used instead: in 32-bit mode with no prescaler it
+/// counts at the 16 MHz HFCLK, which is also the core clock.
+pub struct CycleCounter {
+    _private: (),
+}
+
+impl CycleCounter {
+    pub(super) const fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Starts TIMER0, owning the peripheral.
+    fn start(_timer: pac::TIMER0) {
+        let timer = Self::timer();
+        timer.tasks_stop.write(|w| unsafe { w.bits(1) });
+        // Timer mode, 32-bit width, 16 MHz
+        timer.mode.write(|w| unsafe { w.bits(0) });
+        timer.bitmode.write(|w| unsafe { w.bits(3) });
+        timer.prescaler.write(|w| unsafe { w.bits(0) });
+        timer.tasks_clear.write(|w| unsafe { w.bits(1) });
+        timer.tasks_start.write(|w| unsafe { w.bits(1) });
+    }
+
+    pub fn reset(&self) {
+        Self::timer().tasks_clear.write(|w| unsafe { w.bits(1) });
+    }
+
+    pub fn read(&self) -> u32 {
+        let timer = Self::timer();
+        // The counter value is only readable through a capture register
+        timer.tasks_capture[0].write(|w| unsafe { w.bits(1) });
+        timer.cc[0].read().bits()
+    }
+
+    fn timer() -> &'static pac::timer0::RegisterBlock {
+        // SAFETY: TIMER0 is only used by the cycle counter, see `start`
+        unsafe { &*pac::TIMER0::ptr() }
+    }
+}
+
+pub fn setup(device: pac::Peripherals, _dcb: DCB, _dwt: DWT) -> Clocks {
+    // The core runs from the 16 MHz HFCLK.
+    CycleCounter::start(device.TIMER0);
+    super::check_cycle_counter();
+    Clocks {
+        sysclk_hz: CORE_CLOCK_HZ,
+        hclk_mhz: CORE_CLOCK_HZ / 1_000_000,
+    }
+}
@@ -0,0 +1,69 @@
+//! Board support for the profiling scenarios.
+//!
+//! Exactly one board feature must be enabled (checked by `build.rs`, which also selects the
+//! board memory layout). Each board module provides:
+//! - `pac`: the device crate used by the RTIC application;
+//! - `NAME`, `CORE` and `ARCH`: identifiers reported in the run metadata;
+//! - `CORE_CLOCK_HZ`: the core clock frequency configured by `setup`, the rate of the `dwt-mono` monotonic;
+//! - `ISR_SWITCH_IRQ`: the interrupt pended by the `isr-switch` scenario;
+//! - `CycleCounter`: the free-running counter used to timestamp the scenarios, with `reset()` and `read()`,
+//!   whose only instance is [`CYCLE_COUNTER`];
+//! - `setup`: the clock tree and cycle counter configuration, returning the resulting [`Clocks`].
+
+#[cfg(all(feature = "tim2", not(feature = "olimex-h405")))]
+compile_error!("The tim2 overhead is only available on the olimex-h405 board");
+#[cfg(all(feature = "dwt-mono", feature = "microbit"))]
+compile_error!("The dwt-mono monotonic requires a DWT cycle counter, which the microbit board lacks");
+#[cfg(all(feature = "dwt-mono", any(feature = "lm3s6965evb", feature = "mps2-an505")))]
+compile_error!("The dwt-mono monotonic requires a DWT cycle counter, which QEMU does not model");
+
+#[cfg(feature = "olimex-h405")]
+mod dwt;
+
+#[cfg(feature = "olimex-h405")]
+mod olimex_h405;
+#[cfg(feature = "olimex-h405")]
+pub use olimex_h405::*;
+
+#[cfg(feature = "lm3s6965evb")]
+mod lm3s6965evb;
+#[cfg(feature = "lm3s6965evb")]
+pub use lm3s6965evb::*;
+
+#[cfg(feature = "microbit")]
+mod microbit;
+#[cfg(feature = "microbit")]
+pub use microbit::*;
+
+#[cfg(feature = "mps2-an505")]
+mod mps2_an505;
+#[cfg(feature = "mps2-an505")]
+pub use mps2_an505::*;
+
+/// Target triple the application was built for.
+pub const TARGET: &str = env!("PROFILING_TARGET");
+
+/// The board cycle counter, counting once `setup` has returned.
+pub static CYCLE_COUNTER: CycleCounter = CycleCounter::new();
+
+/// Panics if the cycle counter does not advance, e.g. because the emulator does not model it:
+/// the scenarios would report 0 cycles.
+#[cfg(any(feature = "lm3s6965evb", feature = "microbit", feature = "mps2-an505"))]
+fn check_cycle_counter() {
+    let start = CYCLE_COUNTER.read();
+    cortex_m::asm::delay(10_000);
+    if CYCLE_COUNTER.read() == start {
+        defmt::panic!("The cycle counter of the {} board does not advance", NAME);
+    }
+}
+
+/// Clock frequencies resulting from the board clock setup.
+pub struct Clocks {
+    /// Frequency of the clock feeding SysTick, in Hz.
+    pub sysclk_hz: u32,
+    /// Frequency of the core clock (the `CycleCounter` rate), in MHz.
+    pub hclk_mhz: u32,
+    /// Frequency of the TIM2 peripheral clock, in Hz.
+    #[cfg(feature = "tim2")]
+    pub tim2_clk_hz: u32,
+}
@@ -0,0 +1,66 @@
+//! Arm MPS2+ with the AN505 FPGA image: Cortex-M33 with FPU (`thumbv8m.main-none-eabihf`).
+//!
+//! Mainly meant to be run on the QEMU `mps2-an505` machine. The application runs in the Secure
+//! state, which is the state the core boots in.
+
+use super::Clocks;
+use cortex_m::peripheral::{DCB, DWT};
+
+pub mod pac;
+
+pub const NAME: &str = "mps2-an505";
+pub const CORE: &str = "cortex-m33";
+pub const ARCH: &str = "armv8-m.main";
+
+pub const CORE_CLOCK_HZ: u32 = 20_000_000;
+
+pub const ISR_SWITCH_IRQ: pac::Interrupt = pac::Interrupt::UART2RX;
+
+// CMSDK APB timer 0 of the SSE-200, through its Secure alias
+const TIMER0_CTRL: *mut u32 = 0x5000_0000 as *mut u32;
+const TIMER0_VALUE: *mut u32 = 0x5000_0004 as *mut u32;
+const TIMER0_RELOAD: *mut u32 = 0x5000_0008 as *mut u32;
+
+/// Free-running counter of core clock cycles.
+///
+/// QEMU does not model the DWT cycle counter, hence the CMSDK APB timer 0 is used instead: it
+/// counts down at the 20 MHz main clock, which is also the core clock, reloading the largest
+/// value when it reaches zero. Its interrupt is left disabled.
+pub struct CycleCounter {
+    _private: (),
+}
+
+impl CycleCounter {
+    pub(super) const fn new() -> Self {
+        Self { _private: () }
+    }
+
+    fn start() {
+        // SAFETY: the timer is only used by the cycle counter
+        unsafe {
+            TIMER0_CTRL.write_volatile(0);
+            TIMER0_RELOAD.write_volatile(u32::MAX);
+            TIMER0_VALUE.write_volatile(u32::MAX);
+            // Enabled, clocked by the main clock
+            TIMER0_CTRL.write_volatile(1);
+        }
+    }
+
+    pub fn reset(&self) {
+        unsafe { TIMER0_VALUE.write_volatile(u32::MAX) };
+    }
+
+    pub fn read(&self) -> u32 {
+        u32::MAX - unsafe { TIMER0_VALUE.read_volatile() }
+    }
+}
+
+pub fn setup(_device: pac::Peripherals, _dcb: DCB, _dwt: DWT) -> Clocks {
+    // The FPGA image provides a fixed 20 MHz system clock.
+    CycleCounter::start();
+    super::check_cycle_counter();
+    Clocks {
+        sysclk_hz: CORE_CLOCK_HZ,
+        hclk_mhz: CORE_CLOCK_HZ / 1_000_000,
+    }
+}
@@ -0,0 +1,79 @@
+//! Minimal device definitions for the AN505 image, providing what RTIC and `cortex-m-rt` need:
+//! the interrupt numbers, the NVIC priority bits and the interrupt vector table.
+//!
+//! Only the UART interrupts are defined, the application does not use the UARTs and uses their
+//! interrupt lines as RTIC dispatchers and as the `isr-switch` interrupt.
+
+#![allow(non_camel_case_types)]
+
+/// Number of priority bits implemented by the NVIC.
+pub const NVIC_PRIO_BITS: u8 = 3;
+
+/// External interrupts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u16)]
+pub enum Interrupt {
+    UART0RX = 32,
+    UART0TX = 33,
+    UART1RX = 34,
+    UART1TX = 35,
+    UART2RX = 36,
+    UART2TX = 37,
+}
+
+unsafe impl cortex_m::interrupt::InterruptNumber for Interrupt {
+    #[inline(always)]
+    fn number(self) -> u16 {
+        self as u16
+    }
+}
+
+// Same export as `svd2rust` generated device crates, RTIC checks the interrupt names through it
+pub use self::Interrupt as interrupt;
+
+/// Device peripherals, none of them is used by the application.
+pub struct Peripherals {}
+
+impl Peripherals {
+    /// Unchecked version of a `take` method, for RTIC.
+    ///
+    /// # Safety
+    ///
+    /// There are no peripherals to alias.
+    #[inline]
+    pub unsafe fn steal() -> Self {
+        Peripherals {}
+    }
+}
+
+// Defaulted to `DefaultHandler` by `device.x`
+unsafe extern "C" {
+    fn UART0RX();
+    fn UART0TX();
+    fn UART1RX();
+    fn UART1TX();
+    fn UART2RX();
+    fn UART2TX();
+}
+
+#[doc(hidden)]
+#[derive(Copy, Clone)]
+pub union Vector {
+    _handler: unsafe extern "C" fn(),
+    _reserved: u32,
+}
+
+#[doc(hidden)]
+#[unsafe(link_section = ".vector_table.interrupts")]
+#[unsafe(no_mangle)]
+pub static __INTERRUPTS: [Vector; 38] = {
+    // Interrupts 0..32 belong to the (unused) subsystem peripherals
+    let mut vectors = [Vector { _reserved: 0 }; 38];
+    vectors[Interrupt::UART0RX as usize] = Vector { _handler: UART0RX };
+    vectors[Interrupt::UART0TX as usize] = Vector { _handler: UART0TX };
+    vectors[Interrupt::UART1RX as usize] = Vector { _handler: UART1RX };
+    vectors[Interrupt::UART1TX as usize] = Vector { _handler: UART1TX };
+    vectors[Interrupt::UART2RX as usize] = Vector { _handler: UART2RX };
+    vectors[Interrupt::UART2TX as usize] = Vector { _handler: UART2TX };
+    vectors
+};
@@ -0,0 +1,40 @@
+//! Olimex STM32-H405: STM32F405RG, Cortex-M4F (`thumbv7em-none-eabihf`).
+
+use super::Clocks;
+use cortex_m::peripheral::{DCB, DWT};
+use rtic_monotonics::fugit::RateExtU32 as _;
+use stm32f4xx_hal::rcc::RccExt;
+
+pub use super::dwt::CycleCounter;
+pub use stm32f4xx_hal::pac;
+
+pub const NAME: &str = "olimex-h405";
+pub const CORE: &str = "cortex-m4f";
+pub const ARCH: &str = "armv7e-m";
+
+pub const CORE_CLOCK_HZ: u32 = 168_000_000;
+
+pub const ISR_SWITCH_IRQ: pac::Interrupt = pac::Interrupt::EXTI0;
+
+pub fn setup(device: pac::Peripherals, dcb: DCB, dwt: DWT) -> Clocks {
+    let rcc = device.RCC.constrain();
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.MHz())
+        .sysclk(CORE_CLOCK_HZ.Hz())
+        .pclk1(42.MHz())
+        .freeze();
+
+    // With dwt-mono, CYCCNT is enabled by the monotonic
+    #[cfg(not(feature = "dwt-mono"))]
+    CycleCounter::start(dcb, dwt);
+    #[cfg(feature = "dwt-mono")]
+    let _ = (dcb, dwt);
+
+    Clocks {
+        sysclk_hz: clocks.sysclk().to_Hz(),
+        hclk_mhz: clocks.hclk().to_MHz(),
+        #[cfg(feature = "tim2")]
+        tim2_clk_hz: clocks.timclk1().to_Hz(),
+    }
+}
@@ -0,0 +1,112 @@
+use crate::time::{Instant, Mono};
+use core::cell::Cell;
+use critical_section::Mutex;
+use rtic_monotonics::Monotonic;
+use rtic_sync::signal::{Signal, SignalReader, SignalWriter};
+
+/// Event releasing, at once, all the waiters waiting on it when signalled.
+///
+/// Each of the `N` waiters (at most 32) has its own `Signal`, written by the signaler for the
+/// waiters registered by a `wait`. Create statically allocated instances with
+/// [`make_broadcast!`](crate::make_broadcast).
+pub struct Broadcast<const N: usize> {
+    signals: [Signal<()>; N],
+    /// Bit mask of the waiting waiters
+    waiting: Mutex<Cell<u32>>,
+}
+
+impl<const N: usize> Broadcast<N> {
+    pub const fn new() -> Self {
+        assert!(N > 0 && N <= 32, "Broadcast supports from 1 to 32 waiters");
+        Self {
+            signals: [const { Signal::new() }; N],
+            waiting: Mutex::new(Cell::new(0)),
+        }
+    }
+
+    pub fn split(
+        &self,
+        activation_watchdog: SignalWriter<'static, Instant>,
+    ) -> ([BroadcastWaiter<'_>; N], BroadcastSignaler<'_, N>) {
+        let signals = self.signals.each_ref().map(Signal::split);
+        let writers = signals.each_ref().map(|(writer, _)| writer.clone());
+        let mut index = 0;
+        let waiters = signals.map(|(_, reader)| {
+            let mask = 1 << index;
+            index += 1;
+            BroadcastWaiter {
+                inner: reader,
+                waiting: &self.waiting,
+                mask,
+            }
+        });
+
+        (
+            waiters,
+            BroadcastSignaler {
+                inner: writers,
+                waiting: &self.waiting,
+                activation_watchdog,
+            },
+        )
+    }
+}
+
+impl<const N: usize> Default for Broadcast<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Creates a statically allocated [`Broadcast`] for `$waiters` waiters and splits it, in the
+/// style of `rtic_sync::make_signal!`.
+#[macro_export]
+macro_rules! make_broadcast {
+    ($waiters:expr, $activation_watchdog:expr) => {{
+        static BROADCAST: $crate::broadcast::Broadcast<{ $waiters }> = $crate::broadcast::Broadcast::new();
+        BROADCAST.split($activation_watchdog)
+    }};
+}
+
+pub struct BroadcastWaiter<'a> {
+    inner: SignalReader<'a, ()>,
+    waiting: &'a Mutex<Cell<u32>>,
+    mask: u32,
+}
+
+impl<'a> BroadcastWaiter<'a> {
+    /// Waits for the next signal.
+    ///
+    /// Cancelling the wait leaves the waiter registered until the next signal, which then
+    /// releases its next wait at once.
+    pub async fn wait(&mut self) {
+        critical_section::with(|cs| {
+            let waiting = self.waiting.borrow(cs);
+            waiting.set(waiting.get() | self.mask);
+        });
+        self.inner.wait().await;
+    }
+}
+
+pub struct BroadcastSignaler<'a, const N: usize> {
+    inner: [SignalWriter<'a, ()>; N],
+    waiting: &'a Mutex<Cell<u32>>,
+    activation_watchdog: SignalWriter<'static, Instant>,
+}
+
+impl<'a, const N: usize> BroadcastSignaler<'a, N> {
+    /// Releases all the waiting waiters, returning how many.
+    pub fn signal(&mut self) -> u32 {
+        critical_section::with(|cs| {
+            let waiting = self.waiting.borrow(cs).replace(0);
+            for (index, writer) in self.inner.iter_mut().enumerate() {
+                if waiting & (1 << index) != 0 {
+                    writer.write(());
+                }
+            }
+            // Signal activation to the related deadline watchdog
+            self.activation_watchdog.write(Mono::now());
+            waiting.count_ones()
+        })
+    }
+}
@@ -0,0 +1,115 @@
+use crate::time::{Duration, Instant, Mono};
+use rtic_monotonics::Monotonic;
+use rtic_sync::signal::{SignalReader, SignalWriter};
+
+/// Deadline miss of a sporadic task activation.
+#[derive(Clone, Copy)]
+pub struct DeadlineMiss {
+    /// Activation instant, written by the signaler of the task
+    pub activation: Instant,
+    /// Completion of the task past its absolute deadline
+    pub lateness: Duration,
+}
+
+/// Activations and deadline misses watched since the last reset.
+#[derive(Clone, Copy, Default)]
+pub struct DeadlineStats {
+    pub activations: u32,
+    pub misses: u32,
+    /// Worst-case lateness of the missed deadlines, in microseconds
+    pub wc_lateness_us: u64,
+}
+
+/// Watchdog of the relative deadline of a sporadic task.
+///
+/// It is armed by each activation instant written by the signaler of the task (the
+/// `activation_watchdog` of `TaskSemaphoreSignaler` and `EventQueueSignaler`), and disarmed by
+/// the task through its [`CompletionReporter`] once the activation is handled. `arm` and `watch`
+/// must run in a task of higher priority than the watched one, so that it is armed before the
+/// watched task runs.
+pub struct DeadlineWatchdog {
+    deadline: Duration,
+    activations: SignalReader<'static, Instant>,
+    completions: SignalReader<'static, Instant>,
+    stats: DeadlineStats,
+}
+
+impl DeadlineWatchdog {
+    pub fn new(
+        deadline: Duration,
+        activations: SignalReader<'static, Instant>,
+        (completion_writer, completions): (SignalWriter<'static, Instant>, SignalReader<'static, Instant>),
+    ) -> (Self, CompletionReporter) {
+        (
+            Self {
+                deadline,
+                activations,
+                completions,
+                stats: DeadlineStats::default(),
+            },
+            CompletionReporter {
+                inner: completion_writer,
+            },
+        )
+    }
+
+    /// Waits for the next activation, to be watched by [`watch`](Self::watch).
+    pub async fn arm(&mut self) -> Instant {
+        self.activations.wait().await
+    }
+
+    /// Watches `activation` until its completion.
+    ///
+    /// A miss is recorded in the stats and returned with its lateness once the task completes,
+    /// for the caller to report it.
+    pub async fn watch(&mut self, activation: Instant) -> Result<(), DeadlineMiss> {
+        let deadline = activation + self.deadline;
+        self.stats.activations += 1;
+
+        let completion = match Mono::timeout_at(deadline, Self::completion(&mut self.completions, activation)).await {
+            Ok(completion) => completion,
+            Err(_) => Self::completion(&mut self.completions, activation).await,
+        };
+        if completion <= deadline {
+            Ok(())
+        } else {
+            Err(self.record_miss(activation, completion - deadline))
+        }
+    }
+
+    pub fn stats(&self) -> DeadlineStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = DeadlineStats::default();
+    }
+
+    /// Waits for the completion of `activation`, skipping the completions of earlier ones.
+    async fn completion(completions: &mut SignalReader<'static, Instant>, activation: Instant) -> Instant {
+        loop {
+            let completion = completions.wait().await;
+            if completion >= activation {
+                return completion;
+            }
+        }
+    }
+
+    fn record_miss(&mut self, activation: Instant, lateness: Duration) -> DeadlineMiss {
+        self.stats.misses += 1;
+        self.stats.wc_lateness_us = self.stats.wc_lateness_us.max(u64::from(lateness.to_micros()));
+        DeadlineMiss { activation, lateness }
+    }
+}
+
+/// Disarms the [`DeadlineWatchdog`] of a task.
+pub struct CompletionReporter {
+    inner: SignalWriter<'static, Instant>,
+}
+
+impl CompletionReporter {
+    /// Reports the completion of the current activation of the task.
+    pub fn complete(&mut self) {
+        self.inner.write(Mono::now());
+    }
+}
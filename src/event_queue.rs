@@ -1,66 +1,297 @@
-use crate::time::{Instant, Mono};
-use core::{
-    mem::MaybeUninit,
-    sync::atomic::{AtomicBool, Ordering},
-};
+use crate::inter_arrival::{DeferredMit, MinInterArrival, MitPolicy, MitStats, MitViolation, ReleaseSpacing};
+use crate::overrun::OverrunStats;
+use crate::time::{Duration, Instant, Mono, TimeoutError};
+use core::cell::{Cell, RefCell};
+use critical_section::Mutex;
 use rtic_monotonics::Monotonic;
 use rtic_sync::signal::{Signal, SignalReader, SignalWriter};
 
-pub type EventType = ();
-pub struct EventQueue;
+/// What `signal` does with an event when the queue is full.
+#[derive(Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum OverflowPolicy {
+    /// Hand the new event back to the signaler as an error.
+    Reject,
+    /// Drop the oldest event to make room for the new one, counting it as dropped.
+    DropOldest,
+    /// Drop the new event, counting it as dropped.
+    CountOverrun,
+}
+
+/// Event handed back to the signaler.
+#[derive(defmt::Format)]
+pub enum SignalError<T> {
+    /// The queue was full, see [`OverflowPolicy::Reject`].
+    QueueFull(T),
+    /// The event arrived too early, see [`MitPolicy::Reject`].
+    MitViolation(T, MitViolation),
+    /// The event found the queue full and the overflow policy dropped the returned event: the
+    /// new one with `CountOverrun`, the oldest pending one with `DropOldest`. See
+    /// [`EventQueueSignaler::set_overrun_error`].
+    Overrun(T),
+}
+
+/// Fixed-capacity ring buffer of pending events.
+struct Ring<T, const N: usize> {
+    slots: [Option<T>; N],
+    head: usize,
+    len: usize,
+    dropped: u32,
+}
+
+impl<T, const N: usize> Ring<T, N> {
+    fn push(&mut self, evt: T) {
+        self.slots[(self.head + self.len) % N] = Some(evt);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let evt = self.slots[self.head].take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        evt
+    }
+}
+
+/// Bounded FIFO queue of events of type `T`, holding at most `N` pending events.
+///
+/// The queue is split into a waiter, which receives the events in order, and signalers, which
+/// send them. The events are copied in and out of the ring buffer within a critical section,
+/// and a `Signal` wakes the waiter up.
+///
+/// Create statically allocated instances with [`make_event_queue!`](crate::make_event_queue).
+pub struct EventQueue<T, const N: usize> {
+    ring: Mutex<RefCell<Ring<T, N>>>,
+    wake: Signal<()>,
+    policy: OverflowPolicy,
+    deferred_mit: DeferredMit,
+}
 
-static mut EVENT_QUEUE: MaybeUninit<Signal<EventType>> = MaybeUninit::uninit();
-static INITIALIZED: AtomicBool = AtomicBool::new(false);
+impl<T, const N: usize> EventQueue<T, N> {
+    pub const fn new(policy: OverflowPolicy) -> Self {
+        assert!(N > 0, "EventQueue capacity must be at least 1");
+        Self {
+            ring: Mutex::new(RefCell::new(Ring {
+                slots: [const { None }; N],
+                head: 0,
+                len: 0,
+                dropped: 0,
+            })),
+            wake: Signal::new(),
+            policy,
+            deferred_mit: Mutex::new(Cell::new(None)),
+        }
+    }
 
-impl EventQueue {
-    // The hint is safe since the implementation never leaks the reference out and its used atomically
-    #[allow(static_mut_refs)]
-    pub fn init(
+    pub fn split(
+        &self,
         activation_watchdog: SignalWriter<'static, Instant>,
-    ) -> (EventQueueWaiter<'static>, EventQueueSignaler<'static>) {
-        let (writer, reader) = if INITIALIZED
-            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
-            .is_ok()
-        {
-            // SAFETY: The CAS operation guarantees at most one initialization even with competing threads, hence if we reach this branch we
-            // are guaranteed to be the only initializers of the static signal, and splitting is safe.
-            unsafe { EVENT_QUEUE.write(Signal::new()).split() }
-        } else {
-            defmt::panic!("Multiple EventQueue initialization");
-        };
+    ) -> (EventQueueWaiter<'_, T, N>, EventQueueSignaler<'_, T, N>) {
+        let (writer, reader) = self.wake.split();
 
         (
-            EventQueueWaiter { inner: reader },
+            EventQueueWaiter {
+                ring: &self.ring,
+                inner: reader,
+                spacing: ReleaseSpacing::new(&self.deferred_mit),
+            },
             EventQueueSignaler {
+                ring: &self.ring,
                 inner: writer,
+                policy: self.policy,
                 activation_watchdog,
+                deferred_mit: &self.deferred_mit,
+                min_inter_arrival: None,
+                overruns: OverrunStats::default(),
+                overrun_error: false,
             },
         )
     }
 }
 
-pub struct EventQueueWaiter<'a> {
-    inner: SignalReader<'a, EventType>,
+/// Creates a statically allocated [`EventQueue`] of `$capacity` events of type `$T` and splits
+/// it, in the style of `rtic_sync::make_signal!`.
+///
+/// Each call site allocates its own instance, whose activations are written to
+/// `activation_watchdog`.
+#[macro_export]
+macro_rules! make_event_queue {
+    ($T:ty, $capacity:expr, $policy:expr, $activation_watchdog:expr) => {{
+        static EVENT_QUEUE: $crate::event_queue::EventQueue<$T, { $capacity }> =
+            $crate::event_queue::EventQueue::new($policy);
+        EVENT_QUEUE.split($activation_watchdog)
+    }};
 }
 
-impl<'a> EventQueueWaiter<'a> {
-    pub async fn wait(&mut self) {
-        self.inner.wait().await;
+pub struct EventQueueWaiter<'a, T, const N: usize> {
+    ring: &'a Mutex<RefCell<Ring<T, N>>>,
+    inner: SignalReader<'a, ()>,
+    spacing: ReleaseSpacing<'a>,
+}
+
+impl<'a, T, const N: usize> EventQueueWaiter<'a, T, N> {
+    /// Waits for the oldest pending event.
+    pub async fn wait(&mut self) -> T {
+        // The signal only wakes the waiter up, the events are taken from the ring buffer: a
+        // wake-up left over by events already received finds it empty and waits again. A
+        // pending event is only taken once deferred: a timed wait expiring meanwhile leaves it
+        // pending.
+        loop {
+            if !self.is_empty() {
+                self.spacing.release().await;
+                if let Some(evt) = self.try_wait() {
+                    return evt;
+                }
+            }
+            self.inner.wait().await;
+        }
+    }
+
+    /// Waits for the oldest pending event until `instant`, removing the timer queue entry if an
+    /// event arrives first.
+    pub async fn wait_until(&mut self, instant: Instant) -> Result<T, TimeoutError> {
+        Mono::timeout_at(instant, self.wait()).await.map_err(|_| TimeoutError)
+    }
+
+    /// Waits for the oldest pending event for at most `duration`, see
+    /// [`wait_until`](Self::wait_until).
+    pub async fn wait_timeout(&mut self, duration: Duration) -> Result<T, TimeoutError> {
+        Mono::timeout_after(duration, self.wait()).await.map_err(|_| TimeoutError)
+    }
+
+    /// Takes the oldest pending event, if any, without waiting: the deferral of the early
+    /// events does not apply.
+    pub fn try_wait(&mut self) -> Option<T> {
+        critical_section::with(|cs| self.ring.borrow_ref_mut(cs).pop())
+    }
+
+    /// Number of pending events.
+    pub fn len(&self) -> usize {
+        critical_section::with(|cs| self.ring.borrow_ref(cs).len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of events dropped by the `DropOldest` and `CountOverrun` policies.
+    pub fn dropped(&self) -> u32 {
+        critical_section::with(|cs| self.ring.borrow_ref(cs).dropped)
     }
 }
 
-#[derive(Clone)]
-pub struct EventQueueSignaler<'a> {
-    inner: SignalWriter<'a, EventType>,
+pub struct EventQueueSignaler<'a, T, const N: usize> {
+    ring: &'a Mutex<RefCell<Ring<T, N>>>,
+    inner: SignalWriter<'a, ()>,
+    policy: OverflowPolicy,
     activation_watchdog: SignalWriter<'static, Instant>,
+    deferred_mit: &'a DeferredMit,
+    min_inter_arrival: Option<MinInterArrival>,
+    overruns: OverrunStats,
+    overrun_error: bool,
 }
 
-impl<'a> EventQueueSignaler<'a> {
-    pub fn signal(&mut self, evt: EventType) {
-        critical_section::with(|_cs| {
-            self.inner.write(evt);
-            // Signal activation to the related deadline watchdog
-            self.activation_watchdog.write(Mono::now());
+impl<'a, T, const N: usize> Clone for EventQueueSignaler<'a, T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            ring: self.ring,
+            inner: self.inner.clone(),
+            policy: self.policy,
+            activation_watchdog: self.activation_watchdog.clone(),
+            deferred_mit: self.deferred_mit,
+            min_inter_arrival: self.min_inter_arrival.clone(),
+            overruns: OverrunStats::default(),
+            overrun_error: self.overrun_error,
+        }
+    }
+}
+
+impl<'a, T, const N: usize> EventQueueSignaler<'a, T, N> {
+    /// Checks the events against the minimum inter-arrival time `mit`, handling the early ones
+    /// according to `policy`.
+    pub fn set_min_inter_arrival(&mut self, mit: Duration, policy: MitPolicy) {
+        self.min_inter_arrival = Some(MinInterArrival::new(mit, policy));
+        let deferred_mit = (policy == MitPolicy::Defer).then_some(mit);
+        critical_section::with(|cs| self.deferred_mit.borrow(cs).set(deferred_mit));
+    }
+
+    /// Minimum inter-arrival time counters, if checked.
+    pub fn mit_stats(&self) -> Option<MitStats> {
+        self.min_inter_arrival.as_ref().map(MinInterArrival::stats)
+    }
+
+    pub fn reset_mit_stats(&mut self) {
+        if let Some(min_inter_arrival) = &mut self.min_inter_arrival {
+            min_inter_arrival.reset_stats();
+        }
+    }
+
+    /// Makes [`signal`](Self::signal) return an error on overruns, besides counting them.
+    pub fn set_overrun_error(&mut self, enabled: bool) {
+        self.overrun_error = enabled;
+    }
+
+    /// Events signalled by this signaler while the queue was full.
+    pub fn overrun_stats(&self) -> OverrunStats {
+        self.overruns
+    }
+
+    pub fn reset_overrun_stats(&mut self) {
+        self.overruns = OverrunStats::default();
+    }
+
+    /// Sends `evt` to the waiter, applying the overflow policy when the queue is full.
+    ///
+    /// `OverflowPolicy::Reject` and `MitPolicy::Reject` return the event in an error: with the
+    /// other overflow policies an event is dropped and counted in [`EventQueueWaiter::dropped`].
+    /// Once [enabled](Self::set_overrun_error), the dropped event is also returned in an error,
+    /// otherwise it is consumed.
+    pub fn signal(&mut self, evt: T) -> Result<(), SignalError<T>> {
+        critical_section::with(|cs| {
+            let now = Mono::now();
+            let mut ring = self.ring.borrow_ref_mut(cs);
+            let full = ring.len == N;
+            // The events the full queue does not take are not checked against the MIT, so that
+            // they do not count as the previous activation of the next event
+            let dropped = match self.policy {
+                OverflowPolicy::Reject if full => {
+                    self.overruns.record(now);
+                    return Err(SignalError::QueueFull(evt));
+                }
+                OverflowPolicy::CountOverrun if full => {
+                    ring.dropped += 1;
+                    Some(evt)
+                }
+                _ => {
+                    if let Some(min_inter_arrival) = &mut self.min_inter_arrival {
+                        if let Err(violation) = min_inter_arrival.check(now) {
+                            return Err(SignalError::MitViolation(evt, violation));
+                        }
+                    }
+                    // DropOldest makes room for the new event
+                    let oldest = if full {
+                        ring.dropped += 1;
+                        ring.pop()
+                    } else {
+                        None
+                    };
+                    ring.push(evt);
+                    self.inner.write(());
+                    // Signal activation to the related deadline watchdog
+                    self.activation_watchdog.write(now);
+                    oldest
+                }
+            };
+
+            if let Some(dropped) = dropped {
+                self.overruns.record(now);
+                if self.overrun_error {
+                    return Err(SignalError::Overrun(dropped));
+                }
+            }
+            Ok(())
         })
     }
 }
@@ -0,0 +1,122 @@
+use crate::time::{Duration, Instant, Mono};
+use core::cell::Cell;
+use critical_section::Mutex;
+use rtic_monotonics::Monotonic;
+
+/// What a signaler does with an activation arriving before the minimum inter-arrival time (MIT)
+/// has elapsed since the previous one.
+#[derive(Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum MitPolicy {
+    /// Drop the activation, `signal` returns an error.
+    Reject,
+    /// Signal the activation, but release the waiter only once the MIT has elapsed since its
+    /// previous release.
+    Defer,
+    /// Signal and release the activation immediately, only counting the violation.
+    Count,
+}
+
+/// Activations checked by a signaler since the last reset.
+#[derive(Clone, Copy, Default, defmt::Format)]
+pub struct MitStats {
+    pub activations: u32,
+    pub violations: u32,
+    /// Shortest separation between two activations, in microseconds
+    pub min_separation_us: Option<u64>,
+}
+
+/// An activation arrived before the minimum inter-arrival time had elapsed.
+#[derive(Clone, Copy, Debug, defmt::Format)]
+pub struct MitViolation {
+    /// Separation from the previous activation, in microseconds
+    pub separation_us: u64,
+}
+
+/// MIT the waiter spaces its releases by, published by a signaler with the `Defer` policy.
+pub(crate) type DeferredMit = Mutex<Cell<Option<Duration>>>;
+
+/// Minimum inter-arrival time check of a signaler.
+#[derive(Clone)]
+pub(crate) struct MinInterArrival {
+    mit: Duration,
+    policy: MitPolicy,
+    last_activation: Option<Instant>,
+    stats: MitStats,
+}
+
+impl MinInterArrival {
+    pub fn new(mit: Duration, policy: MitPolicy) -> Self {
+        Self {
+            mit,
+            policy,
+            last_activation: None,
+            stats: MitStats::default(),
+        }
+    }
+
+    /// Checks the activation at `now` against the previous one.
+    ///
+    /// Only the `Reject` policy returns the violation, the rejected activation is then ignored
+    /// by the next checks.
+    pub fn check(&mut self, now: Instant) -> Result<(), MitViolation> {
+        self.stats.activations += 1;
+        let Some(last) = self.last_activation else {
+            self.last_activation = Some(now);
+            return Ok(());
+        };
+
+        // A deferred previous activation may be released after `now`
+        let separation = now.checked_duration_since(last);
+        let separation_us = separation.map_or(0, |separation| u64::from(separation.to_micros()));
+        self.stats.min_separation_us = Some(self.stats.min_separation_us.map_or(separation_us, |min| min.min(separation_us)));
+        if separation.is_some_and(|separation| separation >= self.mit) {
+            self.last_activation = Some(now);
+            return Ok(());
+        }
+
+        self.stats.violations += 1;
+        match self.policy {
+            MitPolicy::Reject => return Err(MitViolation { separation_us }),
+            MitPolicy::Defer => self.last_activation = Some(last + self.mit),
+            MitPolicy::Count => self.last_activation = Some(now),
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> MitStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = MitStats::default();
+    }
+}
+
+/// Release spacing of a waiter whose signaler defers the early activations.
+pub(crate) struct ReleaseSpacing<'a> {
+    mit: &'a DeferredMit,
+    last_release: Option<Instant>,
+}
+
+impl<'a> ReleaseSpacing<'a> {
+    pub fn new(mit: &'a DeferredMit) -> Self {
+        Self {
+            mit,
+            last_release: None,
+        }
+    }
+
+    /// Delays the current release until the deferred MIT has elapsed since the previous one.
+    pub async fn release(&mut self) {
+        let Some(mit) = critical_section::with(|cs| self.mit.borrow(cs).get()) else {
+            return;
+        };
+        if let Some(last_release) = self.last_release {
+            let earliest = last_release + mit;
+            if Mono::now() < earliest {
+                Mono::delay_until(earliest).await;
+            }
+        }
+        self.last_release = Some(Mono::now());
+    }
+}
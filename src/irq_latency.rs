@@ -0,0 +1,56 @@
+use core::cell::Cell;
+use cortex_m::peripheral::SYST;
+use critical_section::Mutex;
+use profiled_rtic_monotonics::profiler::{InterruptKind, MonotonicProfiler};
+
+/// Entry latency of the SysTick interrupt, in core clock cycles.
+#[derive(Clone, Copy)]
+pub struct EntryLatency {
+    /// Number of measured interrupts
+    pub count: u32,
+    /// Worst-case latency
+    pub wc_cycles: u32,
+}
+
+impl EntryLatency {
+    const fn new() -> Self {
+        Self {
+            count: 0,
+            wc_cycles: 0,
+        }
+    }
+}
+
+static ENTRY_LATENCY: Mutex<Cell<EntryLatency>> = Mutex::new(Cell::new(EntryLatency::new()));
+
+/// SysTick profiler measuring the cycles from the interrupt request to the handler entry.
+///
+/// SysTick counts core clock cycles down from its reload value, which it loads again when
+/// requesting the interrupt: the cycles counted down since are those spent by the hardware
+/// exception entry and with the interrupt masked, e.g. in critical sections. Only the monotonic of
+/// the `lock-free-signal` feature measures it.
+pub struct EntryLatencyProfiler;
+
+impl MonotonicProfiler for EntryLatencyProfiler {
+    fn on_interrupt_enter(_kind: InterruptKind) {
+        let latency = SYST::get_reload() - SYST::get_current();
+        critical_section::with(|cs| {
+            let entry_latency = ENTRY_LATENCY.borrow(cs);
+            let EntryLatency { count, wc_cycles } = entry_latency.get();
+            entry_latency.set(EntryLatency {
+                count: count + 1,
+                wc_cycles: wc_cycles.max(latency),
+            });
+        });
+    }
+}
+
+/// SysTick entry latency measured since the last reset.
+pub fn entry_latency() -> EntryLatency {
+    critical_section::with(|cs| ENTRY_LATENCY.borrow(cs).get())
+}
+
+/// Restarts the measurement of the SysTick entry latency.
+pub fn reset_entry_latency() {
+    critical_section::with(|cs| ENTRY_LATENCY.borrow(cs).set(EntryLatency::new()));
+}
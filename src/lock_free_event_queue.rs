@@ -0,0 +1,155 @@
+use crate::lock_free_register::AtomicWaker;
+use crate::lock_free_watchdog::LockFreeWatchdogWriter;
+use crate::overrun::OverrunStats;
+use crate::time::Mono;
+use core::cell::UnsafeCell;
+use core::future::poll_fn;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::Poll;
+use rtic_monotonics::Monotonic;
+
+/// Bounded FIFO queue of events of type `T` whose signaler never masks the interrupts, holding
+/// at most `N` pending events, a power of two.
+///
+/// The signaler of an [`EventQueue`](crate::event_queue::EventQueue) copies the events into the
+/// ring buffer within a critical section. Here the ring buffer has a single signaler and a single
+/// waiter, each moving its own index with atomic loads and stores only, available on ARMv6-M
+/// too. The indexes run freely, wrapping around in step with the slots as `N` divides their
+/// range. The new events are rejected when the queue is full, and the activation timestamps of
+/// the others written to a [`LockFreeWatchdog`](crate::lock_free_watchdog::LockFreeWatchdog).
+///
+/// Create statically allocated instances with
+/// [`make_lock_free_event_queue!`](crate::make_lock_free_event_queue).
+pub struct LockFreeEventQueue<T, const N: usize> {
+    slots: [UnsafeCell<MaybeUninit<T>>; N],
+    /// Events taken, only written by the waiter
+    head: AtomicUsize,
+    /// Events sent, only written by the signaler
+    tail: AtomicUsize,
+    waker: AtomicWaker,
+}
+
+// SAFETY: the slots between `head` and `tail` are only accessed by the waiter, the others by the
+// signaler.
+unsafe impl<T: Send, const N: usize> Sync for LockFreeEventQueue<T, N> {}
+
+impl<T, const N: usize> LockFreeEventQueue<T, N> {
+    pub const fn new() -> Self {
+        assert!(N.is_power_of_two(), "LockFreeEventQueue capacity must be a power of two");
+        Self {
+            slots: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    /// Splits the queue into its only waiter and its only signaler, whose activations are written
+    /// to `activation_watchdog`.
+    pub fn split(
+        &self,
+        activation_watchdog: LockFreeWatchdogWriter<'static>,
+    ) -> (LockFreeEventQueueWaiter<'_, T, N>, LockFreeEventQueueSignaler<'_, T, N>) {
+        (
+            LockFreeEventQueueWaiter { queue: self },
+            LockFreeEventQueueSignaler {
+                queue: self,
+                activation_watchdog,
+                overruns: OverrunStats::default(),
+            },
+        )
+    }
+}
+
+impl<T, const N: usize> Default for LockFreeEventQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Creates a statically allocated [`LockFreeEventQueue`] of `$capacity` events of type `$T` and
+/// splits it, in the style of `rtic_sync::make_signal!`.
+#[macro_export]
+macro_rules! make_lock_free_event_queue {
+    ($T:ty, $capacity:expr, $activation_watchdog:expr) => {{
+        static LOCK_FREE_EVENT_QUEUE: $crate::lock_free_event_queue::LockFreeEventQueue<$T, { $capacity }> =
+            $crate::lock_free_event_queue::LockFreeEventQueue::new();
+        LOCK_FREE_EVENT_QUEUE.split($activation_watchdog)
+    }};
+}
+
+pub struct LockFreeEventQueueWaiter<'a, T, const N: usize> {
+    queue: &'a LockFreeEventQueue<T, N>,
+}
+
+impl<'a, T, const N: usize> LockFreeEventQueueWaiter<'a, T, N> {
+    /// Waits for the oldest pending event.
+    pub async fn wait(&mut self) -> T {
+        let queue = self.queue;
+        poll_fn(|cx| {
+            // SAFETY: this is the only waiter of the queue
+            unsafe { queue.waker.register(cx.waker()) };
+
+            // Checked after the registration: an event sent before it is seen here, one sent
+            // after it wakes the registered waker up
+            match self.try_wait() {
+                Some(evt) => Poll::Ready(evt),
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Takes the oldest pending event, if any, without waiting.
+    pub fn try_wait(&mut self) -> Option<T> {
+        let queue = self.queue;
+        let head = queue.head.load(Ordering::Relaxed);
+        if head == queue.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: the slot was written by the signaler before moving `tail` past it, and is not
+        // written again until `head` moves past it
+        let evt = unsafe { (*queue.slots[head % N].get()).assume_init_read() };
+        queue.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(evt)
+    }
+}
+
+pub struct LockFreeEventQueueSignaler<'a, T, const N: usize> {
+    queue: &'a LockFreeEventQueue<T, N>,
+    activation_watchdog: LockFreeWatchdogWriter<'static>,
+    overruns: OverrunStats,
+}
+
+impl<'a, T, const N: usize> LockFreeEventQueueSignaler<'a, T, N> {
+    /// Events signalled by this signaler while the queue was full.
+    pub fn overrun_stats(&self) -> OverrunStats {
+        self.overruns
+    }
+
+    pub fn reset_overrun_stats(&mut self) {
+        self.overruns = OverrunStats::default();
+    }
+
+    /// Sends `evt` to the waiter without masking the interrupts, handing it back if the queue is
+    /// full.
+    pub fn signal(&mut self, evt: T) -> Result<(), T> {
+        let queue = self.queue;
+        let now = Mono::now();
+        let tail = queue.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(queue.head.load(Ordering::Acquire)) == N {
+            self.overruns.record(now);
+            return Err(evt);
+        }
+
+        // SAFETY: the waiter does not read the slot until `tail` moves past it
+        unsafe { (*queue.slots[tail % N].get()).write(evt) };
+        queue.tail.store(tail.wrapping_add(1), Ordering::Release);
+        // SAFETY: this is the only signaler of the queue
+        unsafe { queue.waker.wake() };
+        // Signal activation to the related deadline watchdog
+        self.activation_watchdog.write(now);
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU8, Ordering};
+use core::task::Waker;
+
+/// `reading` value outside the reads
+const NO_SLOT: u8 = 2;
+
+/// Value of type `T` handed over from a single writer to a single reader, which may preempt each
+/// other, with atomic loads and stores only, available on ARMv6-M too.
+///
+/// The value is double buffered: the writer fills the slot that is not published, then publishes
+/// it. The reader announces the slot it reads, which the writer then leaves alone: it overwrites
+/// the published slot instead, which the reader does not read again before its next read.
+pub(crate) struct LockFreeRegister<T> {
+    slots: [UnsafeCell<Option<T>>; 2],
+    /// Slot holding the last value written
+    published: AtomicU8,
+    /// Slot being read, `NO_SLOT` outside the reads
+    reading: AtomicU8,
+}
+
+// SAFETY: a slot is only written while the reader cannot read it, as told by `published` and
+// `reading`.
+unsafe impl<T: Send> Sync for LockFreeRegister<T> {}
+
+impl<T> LockFreeRegister<T> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [UnsafeCell::new(None), UnsafeCell::new(None)],
+            published: AtomicU8::new(0),
+            reading: AtomicU8::new(NO_SLOT),
+        }
+    }
+
+    /// Replaces the value.
+    ///
+    /// # Safety
+    ///
+    /// Only called by the single writer of the register.
+    pub unsafe fn write(&self, value: T) {
+        let current = self.published.load(Ordering::Acquire);
+        let next = 1 - current;
+        if self.reading.load(Ordering::Acquire) == next {
+            // SAFETY: the preempted reader reads the other slot, and reads `published` again
+            // on its next read only
+            unsafe { *self.slots[current as usize].get() = Some(value) };
+        } else {
+            // SAFETY: the reader only reads the published slot, or the one it announced
+            unsafe { *self.slots[next as usize].get() = Some(value) };
+            self.published.store(next, Ordering::Release);
+        }
+    }
+
+    /// Calls `f` with the last value written, if any.
+    ///
+    /// A writer preempting the read leaves the value read unchanged, and the next read gets the
+    /// value it wrote.
+    ///
+    /// # Safety
+    ///
+    /// Only called by the single reader of the register.
+    pub unsafe fn read<R>(&self, f: impl FnOnce(Option<&T>) -> R) -> R {
+        let slot = self.published.load(Ordering::Acquire);
+        // A writer preempting the read before the slot is announced fills it completely, or
+        // fills the other slot
+        self.reading.store(slot, Ordering::Release);
+        // SAFETY: the writer does not write the announced slot
+        let result = f(unsafe { (*self.slots[slot as usize].get()).as_ref() });
+        self.reading.store(NO_SLOT, Ordering::Release);
+        result
+    }
+}
+
+/// Waker of the single waiter of a lock-free primitive, registered by each poll and woken up by
+/// the single signaler, in the style of `AtomicWaker` without its read-modify-write atomics.
+pub(crate) struct AtomicWaker {
+    waker: LockFreeRegister<Waker>,
+}
+
+impl AtomicWaker {
+    pub const fn new() -> Self {
+        Self {
+            waker: LockFreeRegister::new(),
+        }
+    }
+
+    /// Registers the waker of the current poll, for the next [`wake`](Self::wake).
+    ///
+    /// # Safety
+    ///
+    /// Only called by the single waiter.
+    pub unsafe fn register(&self, waker: &Waker) {
+        // SAFETY: the waiter is the writer of the register
+        unsafe { self.waker.write(waker.clone()) };
+    }
+
+    /// Wakes the last registered waker up, if any.
+    ///
+    /// # Safety
+    ///
+    /// Only called by the single signaler.
+    pub unsafe fn wake(&self) {
+        // SAFETY: the signaler is the reader of the register
+        unsafe {
+            self.waker.read(|waker| {
+                if let Some(waker) = waker {
+                    waker.wake_by_ref();
+                }
+            })
+        };
+    }
+}
@@ -0,0 +1,128 @@
+use crate::lock_free_register::AtomicWaker;
+use crate::lock_free_watchdog::LockFreeWatchdogWriter;
+use crate::overrun::OverrunStats;
+use crate::time::Mono;
+use core::future::poll_fn;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::Poll;
+use rtic_monotonics::Monotonic;
+
+/// Binary suspension object whose signaler never masks the interrupts.
+///
+/// The signaler of a [`TaskSemaphore`](crate::task_semaphore::TaskSemaphore) publishes the
+/// release and the activation timestamp within critical sections. Here they are handed over
+/// with atomic loads and stores only, available on ARMv6-M too: the release through a flag,
+/// the activation timestamp through a [`LockFreeWatchdog`](crate::lock_free_watchdog::LockFreeWatchdog).
+///
+/// Create statically allocated instances with
+/// [`make_lock_free_semaphore!`](crate::make_lock_free_semaphore).
+pub struct LockFreeSemaphore {
+    /// Set by the signaler, cleared by the waiter once released
+    pending: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl LockFreeSemaphore {
+    pub const fn new() -> Self {
+        Self {
+            pending: AtomicBool::new(false),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    /// Splits the semaphore into its only waiter and its only signaler, whose activations are
+    /// written to `activation_watchdog`.
+    pub fn split(
+        &self,
+        activation_watchdog: LockFreeWatchdogWriter<'static>,
+    ) -> (LockFreeSemaphoreWaiter<'_>, LockFreeSemaphoreSignaler<'_>) {
+        (
+            LockFreeSemaphoreWaiter { semaphore: self },
+            LockFreeSemaphoreSignaler {
+                semaphore: self,
+                activation_watchdog,
+                overruns: OverrunStats::default(),
+            },
+        )
+    }
+}
+
+impl Default for LockFreeSemaphore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Creates a statically allocated [`LockFreeSemaphore`] and splits it, in the style of
+/// `rtic_sync::make_signal!`.
+#[macro_export]
+macro_rules! make_lock_free_semaphore {
+    ($activation_watchdog:expr) => {{
+        static LOCK_FREE_SEMAPHORE: $crate::lock_free_semaphore::LockFreeSemaphore =
+            $crate::lock_free_semaphore::LockFreeSemaphore::new();
+        LOCK_FREE_SEMAPHORE.split($activation_watchdog)
+    }};
+}
+
+pub struct LockFreeSemaphoreWaiter<'a> {
+    semaphore: &'a LockFreeSemaphore,
+}
+
+impl<'a> LockFreeSemaphoreWaiter<'a> {
+    /// Waits for the next release.
+    pub async fn wait(&mut self) {
+        let semaphore = self.semaphore;
+        poll_fn(|cx| {
+            // SAFETY: this is the only waiter of the semaphore
+            unsafe { semaphore.waker.register(cx.waker()) };
+
+            // Checked after the registration: a release signalled before it is seen here, one
+            // signalled after it wakes the registered waker up
+            if semaphore.pending.load(Ordering::Acquire) {
+                semaphore.pending.store(false, Ordering::Release);
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+pub struct LockFreeSemaphoreSignaler<'a> {
+    semaphore: &'a LockFreeSemaphore,
+    activation_watchdog: LockFreeWatchdogWriter<'static>,
+    overruns: OverrunStats,
+}
+
+impl<'a> LockFreeSemaphoreSignaler<'a> {
+    /// Activations signalled while the previous release was not yet consumed by the waiter.
+    pub fn overrun_stats(&self) -> OverrunStats {
+        self.overruns
+    }
+
+    pub fn reset_overrun_stats(&mut self) {
+        self.overruns = OverrunStats::default();
+    }
+
+    /// Signals an activation, without masking the interrupts.
+    ///
+    /// As with a binary [`TaskSemaphore`](crate::task_semaphore::TaskSemaphore), an activation
+    /// signalled while the release is pending collapses into it, and is counted as an overrun.
+    pub fn signal(&mut self) {
+        let semaphore = self.semaphore;
+        let now = Mono::now();
+        let overrun = semaphore.pending.load(Ordering::Acquire);
+        if !overrun {
+            semaphore.pending.store(true, Ordering::Release);
+        }
+        // SAFETY: this is the only signaler of the semaphore
+        unsafe { semaphore.waker.wake() };
+        // Signal activation to the related deadline watchdog
+        self.activation_watchdog.write(now);
+
+        if overrun {
+            self.overruns.record(now);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::lock_free_register::LockFreeRegister;
+use crate::time::Instant;
+
+/// Slot holding the last activation instant of a task, written by the signaler of a lock-free
+/// primitive without masking the interrupts, and read by its deadline watchdog.
+///
+/// It stands for the `rtic_sync` signal a [`DeadlineWatchdog`](crate::deadline_watchdog::DeadlineWatchdog)
+/// is armed by, which is written within a critical section.
+///
+/// Create statically allocated instances with
+/// [`make_lock_free_watchdog!`](crate::make_lock_free_watchdog).
+pub struct LockFreeWatchdog {
+    activation: LockFreeRegister<Instant>,
+}
+
+impl LockFreeWatchdog {
+    pub const fn new() -> Self {
+        Self {
+            activation: LockFreeRegister::new(),
+        }
+    }
+
+    /// Splits the slot into its only writer and its only reader.
+    pub fn split(&self) -> (LockFreeWatchdogWriter<'_>, LockFreeWatchdogReader<'_>) {
+        (
+            LockFreeWatchdogWriter { watchdog: self },
+            LockFreeWatchdogReader { watchdog: self },
+        )
+    }
+}
+
+impl Default for LockFreeWatchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Creates a statically allocated [`LockFreeWatchdog`] and splits it, in the style of
+/// `rtic_sync::make_signal!`.
+#[macro_export]
+macro_rules! make_lock_free_watchdog {
+    () => {{
+        static LOCK_FREE_WATCHDOG: $crate::lock_free_watchdog::LockFreeWatchdog =
+            $crate::lock_free_watchdog::LockFreeWatchdog::new();
+        LOCK_FREE_WATCHDOG.split()
+    }};
+}
+
+pub struct LockFreeWatchdogWriter<'a> {
+    watchdog: &'a LockFreeWatchdog,
+}
+
+impl<'a> LockFreeWatchdogWriter<'a> {
+    /// Publishes the instant of an activation, without masking the interrupts.
+    pub fn write(&mut self, activation: Instant) {
+        // SAFETY: this is the only writer of the slot
+        unsafe { self.watchdog.activation.write(activation) };
+    }
+}
+
+pub struct LockFreeWatchdogReader<'a> {
+    watchdog: &'a LockFreeWatchdog,
+}
+
+impl<'a> LockFreeWatchdogReader<'a> {
+    /// Instant of the last activation written, if any.
+    pub fn latest(&mut self) -> Option<Instant> {
+        // SAFETY: this is the only reader of the slot
+        unsafe { self.watchdog.activation.read(|activation| activation.copied()) }
+    }
+}
@@ -1,12 +1,37 @@
 #![no_std]
 #![no_main]
 
+mod board;
+#[cfg(feature = "broadcast")]
+mod broadcast;
+mod deadline_watchdog;
 mod event_queue;
+mod inter_arrival;
+#[cfg(feature = "lock-free-signal")]
+mod irq_latency;
+#[cfg(feature = "lock-free-signal")]
+mod lock_free_register;
+#[cfg(feature = "lock-free-signal")]
+mod lock_free_semaphore;
+#[cfg(feature = "lock-free-signal")]
+mod lock_free_event_queue;
+#[cfg(feature = "lock-free-signal")]
+mod lock_free_watchdog;
+mod overrun;
+mod protected_entry;
 mod task_semaphore;
 mod time;
-
+#[cfg(feature = "systick-width")]
+mod systick_width;
+#[cfg(feature = "wake-lateness")]
+mod wake_lateness;
+
+use board::CycleCounter;
+#[cfg(feature = "broadcast")]
+use broadcast::BroadcastWaiter;
 use cortex_m::interrupt;
 use cortex_m_semihosting::debug::{self, EXIT_FAILURE};
+use event_queue::{EventQueueSignaler, EventQueueWaiter};
 
 #[cfg(feature = "rtt")]
 use defmt_rtt as _;
@@ -15,9 +40,110 @@ use defmt_semihosting as _;
 #[cfg(not(any(feature = "rtt", feature = "semihosting")))]
 compile_error!("No global logger selected, enable either the rtt or semihosting feature");
 
-use stm32f4xx_hal as _;
-
 const WCET_THRESHOLD: u32 = 100;
+/// Maximum pending releases of the `TaskSemaphore` in counting mode
+#[cfg(feature = "task-semaphore-counting")]
+const TASK_SEMAPHORE_MAX_COUNT: u32 = 4;
+/// Activations signalled at once to the `TaskSemaphore`: in counting mode, two more than it can
+/// hold, which it drops
+#[cfg(not(feature = "task-semaphore-counting"))]
+const TASK_SEMAPHORE_BURST: u32 = 1;
+#[cfg(feature = "task-semaphore-counting")]
+const TASK_SEMAPHORE_BURST: u32 = TASK_SEMAPHORE_MAX_COUNT + 2;
+/// Every that many activations, the task semaphore waiter overruns its deadline on purpose
+const DEADLINE_OVERRUN_PERIOD: u32 = 10;
+/// Capacity of the `EventQueue`s of the event queue scenario
+const EVENT_QUEUE_CAPACITY: usize = 8;
+/// Capacity of the `EventQueue` of the activation overrun scenario
+#[cfg(feature = "activation-overrun")]
+const OVERRUN_QUEUE_CAPACITY: usize = 4;
+/// Primitives compared by the lock-free signal scenario: task semaphore, lock-free semaphore,
+/// event queue and lock-free event queue
+#[cfg(feature = "lock-free-signal")]
+const SIGNAL_COST_PRIMITIVES: usize = 4;
+/// Waiters of the `Broadcast` of the broadcast scenario
+#[cfg(feature = "broadcast")]
+const BROADCAST_WAITERS: usize = 8;
+/// MIT deferring the second activation of each pair of the deferred timed wait scenario
+#[cfg(feature = "deferred-timed-wait")]
+const DEFERRED_WAIT_MIT: time::Duration = time::Duration::millis(10);
+
+/// Ends a measurement window of `WCET_THRESHOLD` activations, once its results are reported.
+///
+/// Stops the application with `message`, unless the `continuous` feature is enabled: then a new
+/// window starts, and the return value tells whether its worst cases must be reset
+/// (`continuous-reset` feature) or keep accumulating.
+fn end_of_window(activation_count: &mut u32, message: &str) -> bool {
+    if cfg!(not(feature = "continuous")) {
+        defmt::panic!("{}", message);
+    }
+    *activation_count = 0;
+    cfg!(feature = "continuous-reset")
+}
+
+/// Signals `payload` on an event queue once `occupancy` events are pending, returning the cycles
+/// spent in `signal`. The cycle counter is then reset for the waiter to measure its wake-up.
+fn signal_event<T: Copy, const N: usize>(
+    signaler: &mut EventQueueSignaler<'_, T, N>,
+    counter: &CycleCounter,
+    payload: T,
+    occupancy: usize,
+) -> u32 {
+    for _ in 0..occupancy {
+        if signaler.signal(payload).is_err() {
+            defmt::panic!("Event queue full before the measured signal");
+        }
+    }
+    critical_section::with(|_cs| {
+        counter.reset();
+        let rejected = signaler.signal(payload).is_err();
+        let cycles = counter.read();
+        counter.reset();
+        if rejected {
+            defmt::panic!("Measured event rejected by a full event queue");
+        }
+        cycles
+    })
+}
+
+/// Waits for the oldest event, returning the cycles elapsed since its signal, then takes the
+/// events pending behind it.
+async fn wait_event<T, const N: usize>(waiter: &mut EventQueueWaiter<'_, T, N>, counter: &CycleCounter) -> u32 {
+    let evt = waiter.wait().await;
+    let cycles = counter.read();
+    core::hint::black_box(evt);
+    while waiter.try_wait().is_some() {}
+    cycles
+}
+
+/// Cycles spent in `f`, with the interrupts enabled, or `None` if a SysTick interrupt ran
+/// meanwhile.
+#[cfg(feature = "lock-free-signal")]
+fn cycles_without_systick(counter: &CycleCounter, f: impl FnOnce()) -> Option<u32> {
+    let interrupts = irq_latency::entry_latency().count;
+    let start = counter.read();
+    f();
+    let cycles = counter.read().wrapping_sub(start);
+    (irq_latency::entry_latency().count == interrupts).then_some(cycles)
+}
+
+/// Waiters of the broadcast scenario, each taken by the waiter task of its index while waiting
+#[cfg(feature = "broadcast")]
+static BROADCAST_WAITER_SLOTS: critical_section::Mutex<
+    core::cell::RefCell<[Option<BroadcastWaiter<'static>>; BROADCAST_WAITERS]>,
+> = critical_section::Mutex::new(core::cell::RefCell::new([const { None }; BROADCAST_WAITERS]));
+
+/// Body of the broadcast waiter tasks: waits for a broadcast on waiter `index`, recording the
+/// cycles elapsed since its signal as its wake latency.
+#[cfg(feature = "broadcast")]
+async fn wait_broadcast(index: usize, mut latencies: impl rtic::Mutex<T = [u32; BROADCAST_WAITERS]>) {
+    let mut waiter = critical_section::with(|cs| BROADCAST_WAITER_SLOTS.borrow_ref_mut(cs)[index].take())
+        .expect("Broadcast waiter already taken");
+    waiter.wait().await;
+    let cycles = time::get_cycle_counter().read();
+    critical_section::with(|cs| BROADCAST_WAITER_SLOTS.borrow_ref_mut(cs)[index] = Some(waiter));
+    latencies.lock(|latencies| latencies[index] = cycles);
+}
 
 #[panic_handler]
 fn panic(info: &core::panic::PanicInfo) -> ! {
@@ -29,34 +155,67 @@ fn panic(info: &core::panic::PanicInfo) -> ! {
     loop {}
 }
 
-#[rtic::app(
-    device = stm32f4xx_hal::pac,
-    dispatchers = [EXTI1, EXTI2],
-)]
+#[cfg_attr(feature = "olimex-h405", rtic::app(
+    device = crate::board::pac,
+    dispatchers = [EXTI1, EXTI2, EXTI3],
+))]
+#[cfg_attr(feature = "lm3s6965evb", rtic::app(
+    device = crate::board::pac,
+    dispatchers = [SSI0, QEI0, I2C0],
+))]
+#[cfg_attr(feature = "microbit", rtic::app(
+    device = crate::board::pac,
+    dispatchers = [SWI0, SWI1, SWI3],
+))]
+#[cfg_attr(feature = "mps2-an505", rtic::app(
+    device = crate::board::pac,
+    dispatchers = [UART1RX, UART1TX, UART2TX],
+))]
 mod app {
     use crate::{
-        event_queue::{EventQueueSignaler, EventQueueWaiter, EventQueue},
-        task_semaphore::{TaskSemaphoreSignaler, TaskSemaphoreWaiter, TaskSemaphore},
+        board,
+        deadline_watchdog::{CompletionReporter, DeadlineWatchdog},
+        event_queue::{EventQueueSignaler, EventQueueWaiter, OverflowPolicy},
+        inter_arrival::MitPolicy,
+        protected_entry::{EntryCaller, ProtectedProcedure},
+        task_semaphore::{TaskSemaphoreSignaler, TaskSemaphoreWaiter},
         time::{
             Mono, 
             Instant, 
+            Duration,
+            cycles_to_ns,
+            get_cycle_counter,
+            get_hclk_mhz,
         },
         WCET_THRESHOLD,
+        DEADLINE_OVERRUN_PERIOD,
+        EVENT_QUEUE_CAPACITY,
+        end_of_window,
+        signal_event,
+        wait_event,
     };
-    #[cfg(feature = "systick")]
-    use crate::time::{set_dwt_ref, set_hclk_mhz};
-    use core::mem::MaybeUninit;
-    use cortex_m::peripheral::DWT;
-    use stm32f4xx_hal::{
-        interrupt, 
-        pac::NVIC, 
-        rcc::RccExt,
+    #[cfg(feature = "task-semaphore-counting")]
+    use crate::TASK_SEMAPHORE_MAX_COUNT;
+    #[cfg(feature = "lock-free-signal")]
+    use crate::{
+        cycles_without_systick,
+        SIGNAL_COST_PRIMITIVES,
+        lock_free_event_queue::{LockFreeEventQueueSignaler, LockFreeEventQueueWaiter},
+        lock_free_semaphore::{LockFreeSemaphoreSignaler, LockFreeSemaphoreWaiter},
+        lock_free_watchdog::LockFreeWatchdogReader,
     };
-    use rtic_monotonics::{
-        fugit::{
-            RateExtU32 as _,
-        }, 
-        systick::prelude::*};
+    #[cfg(feature = "broadcast")]
+    use crate::{broadcast::BroadcastSignaler, wait_broadcast, BROADCAST_WAITERS, BROADCAST_WAITER_SLOTS};
+    use crate::TASK_SEMAPHORE_BURST;
+    #[cfg(feature = "activation-overrun")]
+    use crate::OVERRUN_QUEUE_CAPACITY;
+    #[cfg(feature = "deferred-timed-wait")]
+    use crate::DEFERRED_WAIT_MIT;
+    use crate::board::CycleCounter;
+    use cortex_m::peripheral::NVIC;
+    #[cfg(all(feature = "continuous", overhead_mono))]
+    use profiled_rtic_monotonics::profiler::LimitAction;
+    use rtic_monotonics::Monotonic;
     use rtic_sync::{
         signal::{
             SignalReader,
@@ -67,121 +226,197 @@ mod app {
 
     // Shared resources go here
     #[shared]
-    struct Shared {}
+    struct Shared {
+        // MIT enforcement: releases and worst-case release latency (ns) of the waiter
+        mit_releases: (u32, u32),
+        // Broadcast: wake latency (cycles) of each waiter in the last release
+        #[cfg(feature = "broadcast")]
+        broadcast_latencies: [u32; BROADCAST_WAITERS],
+        // Lock-free signal: releases consumed by the waiter of each primitive
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_releases: [u32; SIGNAL_COST_PRIMITIVES],
+    }
 
     // Local resources go here
     #[local]
     struct Local {
         // ISR-Switch
-        rise_interrupt_dwt: &'static DWT,
+        rise_interrupt_counter: &'static CycleCounter,
         next_time: Option<Instant>,
-        
-        isr_dwt: &'static DWT,
-        isr_switch_activation_count: u32,
-        switch_cycles: u32,
-        time_ns: f32,
-        wc_isr_switch: f32,
-        isr_hclk_mhz: f32,
 
         // Delay_until
-        delay_until_dwt: &'static DWT,
-        delay_until_hclk_mhz: f32,
+        delay_until_counter: &'static CycleCounter,
+        delay_until_hclk_mhz: u32,
         delay_until_activation_count: u32,
         delay_interval: u32, 
         delay_until_cycles: u32,
-        delay_until_overhead: f32, 
-        wc_delay_until_overhead: f32,
+        delay_until_overhead: u32, 
+        wc_delay_until_overhead: u32,
 
         // Signal rtic_sync
         signal_writer: SignalWriter<'static, ()>,
-        signal_writer_dwt: &'static DWT,
+        signal_writer_counter: &'static CycleCounter,
 
         signal_reader: SignalReader<'static, ()>,
-        signal_reader_dwt: &'static DWT,
+        signal_reader_counter: &'static CycleCounter,
         signal_reader_cycles: u32,
-        signal_reader_time: f32,
-        wc_signal_rtic_sync: f32,
-        signal_reader_hclk_mhz: f32,
+        signal_reader_time: u32,
+        wc_signal_rtic_sync: u32,
+        signal_reader_hclk_mhz: u32,
         signal_reader_activation_count: u32,
 
         // TaskSemaphore
         task_semaphore_waiter: TaskSemaphoreWaiter<'static>,
-        task_semaphore_waiter_dwt: &'static DWT,
+        task_semaphore_waiter_counter: &'static CycleCounter,
 
         task_semaphore_waiter_cycles: u32,
-        task_semaphore_waiter_hclk_mhz: f32,
-        task_semaphore_waiter_time: f32,
-        wc_task_semaphore_waiter: f32,
+        task_semaphore_waiter_hclk_mhz: u32,
+        task_semaphore_waiter_time: u32,
+        wc_task_semaphore_waiter: u32,
         task_semaphore_waiter_activation_count: u32,
         task_semaphore_signaler: TaskSemaphoreSignaler<'static>,
-        task_semaphore_signaler_dwt: &'static DWT,
-
-        // EventQueue
-        event_queue_waiter: EventQueueWaiter<'static>,
-        event_queue_waiter_dwt: &'static DWT,
-
-        event_queue_waiter_cycles: u32,
-        event_queue_waiter_hclk_mhz: f32,
-        event_queue_waiter_time: f32,
-        wc_event_queue_waiter: f32,
-        event_queue_waiter_activation_count: u32,
-        event_queue_signaler: EventQueueSignaler<'static>,
-        event_queue_signaler_dwt: &'static DWT,
+        task_semaphore_signaler_counter: &'static CycleCounter,
+        task_semaphore_completion: CompletionReporter,
+        wc_task_semaphore_completion: u32,
+        task_semaphore_watchdog: DeadlineWatchdog,
+
+        // EventQueue, by payload size
+        event_queue_waiters: (
+            EventQueueWaiter<'static, [u8; 4], EVENT_QUEUE_CAPACITY>,
+            EventQueueWaiter<'static, [u8; 32], EVENT_QUEUE_CAPACITY>,
+            EventQueueWaiter<'static, [u8; 128], EVENT_QUEUE_CAPACITY>,
+        ),
+        event_queue_waiter_counter: &'static CycleCounter,
+        event_queue_waiter_hclk_mhz: u32,
+        event_queue_signalers: (
+            EventQueueSignaler<'static, [u8; 4], EVENT_QUEUE_CAPACITY>,
+            EventQueueSignaler<'static, [u8; 32], EVENT_QUEUE_CAPACITY>,
+            EventQueueSignaler<'static, [u8; 128], EVENT_QUEUE_CAPACITY>,
+        ),
+        event_queue_signaler_counter: &'static CycleCounter,
+        event_queue_signaler_hclk_mhz: u32,
+
+        // Timed wait
+        timed_wait_waiter: TaskSemaphoreWaiter<'static>,
+        timed_wait_waiter_counter: &'static CycleCounter,
+        timed_wait_waiter_hclk_mhz: u32,
+        timed_wait_signaler: TaskSemaphoreSignaler<'static>,
+        timed_wait_signaler_counter: &'static CycleCounter,
+
+        // MIT enforcement
+        mit_waiter: TaskSemaphoreWaiter<'static>,
+        mit_waiter_counter: &'static CycleCounter,
+        mit_waiter_hclk_mhz: u32,
+        mit_signaler: TaskSemaphoreSignaler<'static>,
+        mit_signaler_counter: &'static CycleCounter,
+        mit_signaler_hclk_mhz: u32,
+        // Deferred timed wait
+        deferred_wait_waiter: TaskSemaphoreWaiter<'static>,
+        deferred_wait_signaler: TaskSemaphoreSignaler<'static>,
+
+        // ProtectedEntry, holding the number of pending samples
+        protected_entry_caller: EntryCaller<'static, u32>,
+        protected_entry_caller_counter: &'static CycleCounter,
+        protected_entry_caller_hclk_mhz: u32,
+        protected_procedure: ProtectedProcedure<'static, u32>,
+        protected_procedure_counter: &'static CycleCounter,
+        protected_procedure_hclk_mhz: u32,
+
+        // Broadcast
+        #[cfg(feature = "broadcast")]
+        broadcast_signaler: BroadcastSignaler<'static, BROADCAST_WAITERS>,
+
+        // Lock-free signal
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_semaphore_waiter: TaskSemaphoreWaiter<'static>,
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_semaphore_signaler: TaskSemaphoreSignaler<'static>,
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_lock_free_waiter: LockFreeSemaphoreWaiter<'static>,
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_lock_free_signaler: LockFreeSemaphoreSignaler<'static>,
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_queue_waiter: EventQueueWaiter<'static, u32, EVENT_QUEUE_CAPACITY>,
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_queue_signaler: EventQueueSignaler<'static, u32, EVENT_QUEUE_CAPACITY>,
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_lock_free_queue_waiter: LockFreeEventQueueWaiter<'static, u32, EVENT_QUEUE_CAPACITY>,
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_lock_free_queue_signaler: LockFreeEventQueueSignaler<'static, u32, EVENT_QUEUE_CAPACITY>,
+        #[cfg(feature = "lock-free-signal")]
+        signal_cost_lock_free_watchdogs: [LockFreeWatchdogReader<'static>; 2],
+
+        // Activation overrun
+        #[cfg(feature = "activation-overrun")]
+        overrun_semaphore_waiter: TaskSemaphoreWaiter<'static>,
+        #[cfg(feature = "activation-overrun")]
+        overrun_semaphore_signaler: TaskSemaphoreSignaler<'static>,
+        #[cfg(feature = "activation-overrun")]
+        overrun_queue_waiter: EventQueueWaiter<'static, u32, OVERRUN_QUEUE_CAPACITY>,
+        #[cfg(feature = "activation-overrun")]
+        overrun_queue_signaler: EventQueueSignaler<'static, u32, OVERRUN_QUEUE_CAPACITY>,
 
         // Spawn overhead
-        spawn_overhead_dwt: &'static DWT,
+        spawn_overhead_counter: &'static CycleCounter,
         spawn_overhead_cycles: u32,
-        spawn_overhead_ns: f32, 
-        bc_spawn_overhead: f32, 
-        spawn_overhead_hclk_mhz: f32, 
+        spawn_overhead_ns: u32, 
+        bc_spawn_overhead: u32, 
+        spawn_overhead_hclk_mhz: u32, 
         spawn_overhead_activation_count: u32,
 
         // Context switch
-        context_switch_task_dwt: &'static DWT, 
+        context_switch_task_counter: &'static CycleCounter, 
         context_switch_task_cycles: u32, 
-        context_switch_task_hclk_mhz: f32, 
-        context_switch_ns: f32, 
-        wc_context_switch: f32, 
+        context_switch_task_hclk_mhz: u32, 
+        context_switch_ns: u32, 
+        wc_context_switch: u32, 
         context_switch_task_activation_count: u32,
     }
 
-    #[init(local = [
-        dwt_storage: MaybeUninit<DWT> = MaybeUninit::uninit(),
-    ])]
+    #[init]
     fn init(cx: init::Context) -> (Shared, Local) {
         defmt::info!("Init");
 
         // Extract device from context
         let peripherals = cx.device;
-        let mut core = cx.core;
-
-        // Clocks setup
-        let rcc = peripherals.RCC.constrain();
-        let clocks = rcc
-            .cfgr
-            .use_hse(8.MHz())
-            .sysclk(168.MHz())
-            .pclk1(42.MHz())
-            .freeze();
-
-        // HCLK setup
-        let hclk_mhz = clocks.hclk().to_MHz() as f32;
-        #[cfg(feature = "systick")]
-        set_hclk_mhz(hclk_mhz);
-
-        // DWT setup
-        let dwt_ref: &'static DWT = 
-            unsafe { 
-                core.DCB.enable_trace();
-                core.DWT.enable_cycle_counter();
-                cx.local.dwt_storage.write(core.DWT);
-                cx.local.dwt_storage.assume_init_ref()
-            };
-        #[cfg(feature = "systick")]
-        set_dwt_ref(dwt_ref);
+        let core = cx.core;
+
+        // Clocks and cycle counter setup
+        let clocks = board::setup(peripherals, core.DCB, core.DWT);
+        let hclk_mhz = get_hclk_mhz();
+        defmt::assert_eq!(hclk_mhz, clocks.hclk_mhz, "The core clock is not the rate of the cycle counter");
+
+        // Run metadata, to tell apart the results of the different targets
+        defmt::info!(
+            "Board: {}, core: {}, arch: {}, target: {}, core clock: {} MHz",
+            board::NAME, board::CORE, board::ARCH, board::TARGET, hclk_mhz,
+        );
+
+        let counter_ref: &'static CycleCounter = get_cycle_counter();
 
         // Setup monotonic timer
-        Mono::start(core.SYST, clocks.sysclk().to_Hz());
+        #[cfg(not(feature = "tim2"))]
+        Mono::start(core.SYST, clocks.sysclk_hz);
+        #[cfg(feature = "tim2")]
+        Mono::start(clocks.tim2_clk_hz);
+
+        // Keep profiling the monotonic interrupt handler past its sample limit
+        #[cfg(all(feature = "continuous", overhead_mono))]
+        Mono::profile().set_limit_action(if cfg!(feature = "continuous-reset") {
+            LimitAction::ReportAndReset
+        } else {
+            LimitAction::Report
+        });
+
+        // Timer queue load, for the monotonic interrupt handler profiling
+        #[cfg(overhead_mono)]
+        timer_queue_profiling::spawn()
+            .expect("Error spawning timer queue profiling task");
+
+        // Tickless drift profiling setup
+        #[cfg(feature = "tickless-drift")]
+        tickless_drift_profiling::spawn()
+            .expect("Error spawning tickless drift task");
 
         // ISR-Switch profiling setup
         #[cfg(feature = "isr-switch")] 
@@ -193,6 +428,14 @@ mod app {
         delay_until_profiling::spawn()
             .expect("Error spawning delay_until task");
 
+        // Wake lateness profiling setup
+        #[cfg(feature = "wake-lateness")]
+        wake_lateness_profiling::spawn()
+            .expect("Error spawning wake lateness task");
+        #[cfg(feature = "systick-width")]
+        systick_width_profiling::spawn()
+            .expect("Error spawning SysTick width task");
+
         // Signal rtic_sync setup
         let (signal_writer, signal_reader) = make_signal!(());
         #[cfg(feature = "signal-rtic-sync")]
@@ -206,9 +449,26 @@ mod app {
         // Fake watchdog signal for the other synchronization primitives
         let (watchdog_signal_writer, _watchdog_signal_reader) = make_signal!(Instant);
 
+        // Deadline watchdog of the task semaphore waiter
+        let (task_semaphore_activation_writer, task_semaphore_activation_reader) = make_signal!(Instant);
+        let (task_semaphore_watchdog, task_semaphore_completion) = DeadlineWatchdog::new(
+            Duration::millis(100),
+            task_semaphore_activation_reader,
+            make_signal!(Instant),
+        );
+        #[cfg(feature = "deadline-watchdog")]
+        deadline_watchdog_task::spawn()
+            .expect("Error spawning deadline watchdog task");
+
         // Task Semaphore setup
-        let (task_semaphore_waiter, task_semaphore_signaler) = TaskSemaphore::init(
-            watchdog_signal_writer.clone(),
+        #[cfg(not(feature = "task-semaphore-counting"))]
+        let (task_semaphore_waiter, task_semaphore_signaler) = crate::make_task_semaphore!(
+            task_semaphore_activation_writer
+        );
+        #[cfg(feature = "task-semaphore-counting")]
+        let (task_semaphore_waiter, task_semaphore_signaler) = crate::make_task_semaphore!(
+            task_semaphore_activation_writer,
+            max_count = TASK_SEMAPHORE_MAX_COUNT
         );
         #[cfg(feature = "task-semaphore")]
         {
@@ -219,9 +479,17 @@ mod app {
         }
 
         // Event Queue setup
-        let (event_queue_waiter, event_queue_signaler) = EventQueue::init(
-            watchdog_signal_writer.clone(),
+        let (small_waiter, small_signaler) = crate::make_event_queue!(
+            [u8; 4], EVENT_QUEUE_CAPACITY, OverflowPolicy::Reject, watchdog_signal_writer.clone()
+        );
+        let (medium_waiter, medium_signaler) = crate::make_event_queue!(
+            [u8; 32], EVENT_QUEUE_CAPACITY, OverflowPolicy::Reject, watchdog_signal_writer.clone()
+        );
+        let (large_waiter, large_signaler) = crate::make_event_queue!(
+            [u8; 128], EVENT_QUEUE_CAPACITY, OverflowPolicy::Reject, watchdog_signal_writer.clone()
         );
+        let event_queue_waiters = (small_waiter, medium_waiter, large_waiter);
+        let event_queue_signalers = (small_signaler, medium_signaler, large_signaler);
         #[cfg(feature = "event-queue")]
         {
             event_queue_signaler_task::spawn()
@@ -230,6 +498,119 @@ mod app {
                 .expect("Error spawning event queue waiter task");
         }
 
+        // Timed wait setup
+        let (timed_wait_waiter, timed_wait_signaler) = crate::make_task_semaphore!(
+            watchdog_signal_writer.clone()
+        );
+        #[cfg(feature = "timed-wait")]
+        {
+            timed_wait_signaler_task::spawn()
+                .expect("Error spawning timed wait signaler task");
+            timed_wait_waiter_task::spawn()
+                .expect("Error spawning timed wait waiter task");
+        }
+
+        // MIT enforcement setup
+        let (mit_waiter, mit_signaler) = crate::make_task_semaphore!(
+            watchdog_signal_writer.clone()
+        );
+        #[cfg(feature = "mit-enforcement")]
+        {
+            mit_signaler_task::spawn()
+                .expect("Error spawning MIT signaler task");
+            mit_waiter_task::spawn()
+                .expect("Error spawning MIT waiter task");
+        }
+
+        // Deferred timed wait setup, counting both activations of a pair
+        let (deferred_wait_waiter, deferred_wait_signaler) = crate::make_task_semaphore!(
+            watchdog_signal_writer.clone(),
+            max_count = 2
+        );
+        #[cfg(feature = "deferred-timed-wait")]
+        {
+            deferred_wait_signaler_task::spawn()
+                .expect("Error spawning deferred wait signaler task");
+            deferred_wait_waiter_task::spawn()
+                .expect("Error spawning deferred wait waiter task");
+        }
+
+        // Protected entry setup, open once two samples are pending
+        let (protected_entry_caller, protected_procedure) = crate::make_protected_entry!(
+            u32, 0, |samples| *samples >= 2
+        );
+        #[cfg(feature = "protected-entry")]
+        {
+            protected_procedure_task::spawn()
+                .expect("Error spawning protected procedure task");
+            protected_entry_task::spawn()
+                .expect("Error spawning protected entry task");
+        }
+
+        // Broadcast setup, the signaler spawns the waiters
+        #[cfg(feature = "broadcast")]
+        let (broadcast_waiters, broadcast_signaler) = crate::make_broadcast!(
+            BROADCAST_WAITERS, watchdog_signal_writer.clone()
+        );
+        #[cfg(feature = "broadcast")]
+        critical_section::with(|cs| *BROADCAST_WAITER_SLOTS.borrow_ref_mut(cs) = broadcast_waiters.map(Some));
+        #[cfg(feature = "broadcast")]
+        broadcast_signaler_task::spawn()
+            .expect("Error spawning broadcast signaler task");
+
+        // Lock-free signal setup, comparing against a task semaphore and an event queue
+        #[cfg(feature = "lock-free-signal")]
+        let (signal_cost_semaphore_waiter, signal_cost_semaphore_signaler) = crate::make_task_semaphore!(
+            watchdog_signal_writer.clone()
+        );
+        // Stand for the deadline watchdogs of the lock-free primitives, which nobody waits on
+        #[cfg(feature = "lock-free-signal")]
+        let (signal_cost_lock_free_activation_writer, signal_cost_lock_free_activation_reader) =
+            crate::make_lock_free_watchdog!();
+        #[cfg(feature = "lock-free-signal")]
+        let (signal_cost_lock_free_queue_activation_writer, signal_cost_lock_free_queue_activation_reader) =
+            crate::make_lock_free_watchdog!();
+        #[cfg(feature = "lock-free-signal")]
+        let (signal_cost_lock_free_waiter, signal_cost_lock_free_signaler) =
+            crate::make_lock_free_semaphore!(signal_cost_lock_free_activation_writer);
+        #[cfg(feature = "lock-free-signal")]
+        let (signal_cost_queue_waiter, signal_cost_queue_signaler) = crate::make_event_queue!(
+            u32, EVENT_QUEUE_CAPACITY, OverflowPolicy::Reject, watchdog_signal_writer.clone()
+        );
+        #[cfg(feature = "lock-free-signal")]
+        let (signal_cost_lock_free_queue_waiter, signal_cost_lock_free_queue_signaler) =
+            crate::make_lock_free_event_queue!(u32, EVENT_QUEUE_CAPACITY, signal_cost_lock_free_queue_activation_writer);
+        #[cfg(feature = "lock-free-signal")]
+        {
+            signal_cost_task::spawn()
+                .expect("Error spawning signal cost task");
+            signal_cost_semaphore_waiter_task::spawn()
+                .expect("Error spawning signal cost semaphore waiter task");
+            signal_cost_lock_free_waiter_task::spawn()
+                .expect("Error spawning signal cost lock-free waiter task");
+            signal_cost_queue_waiter_task::spawn()
+                .expect("Error spawning signal cost event queue waiter task");
+            signal_cost_lock_free_queue_waiter_task::spawn()
+                .expect("Error spawning signal cost lock-free event queue waiter task");
+        }
+
+        // Activation overrun setup, the signalers report their overruns as errors
+        #[cfg(feature = "activation-overrun")]
+        let (overrun_semaphore_waiter, mut overrun_semaphore_signaler) = crate::make_task_semaphore!(
+            watchdog_signal_writer.clone()
+        );
+        #[cfg(feature = "activation-overrun")]
+        let (overrun_queue_waiter, mut overrun_queue_signaler) = crate::make_event_queue!(
+            u32, OVERRUN_QUEUE_CAPACITY, OverflowPolicy::DropOldest, watchdog_signal_writer.clone()
+        );
+        #[cfg(feature = "activation-overrun")]
+        {
+            overrun_semaphore_signaler.set_overrun_error(true);
+            overrun_queue_signaler.set_overrun_error(true);
+            activation_overrun_task::spawn()
+                .expect("Error spawning activation overrun task");
+        }
+
         // Spawn overhead setup
         #[cfg(feature = "spawn-overhead")]
         spawn_overhead_task::spawn()
@@ -241,93 +622,153 @@ mod app {
             .expect("Error spawning context switch task");
 
         (
-            Shared {},
+            Shared {
+                mit_releases: (0, 0),
+                #[cfg(feature = "broadcast")]
+                broadcast_latencies: [0; BROADCAST_WAITERS],
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_releases: [0; SIGNAL_COST_PRIMITIVES],
+            },
             Local {
                 // ISR-Switch
-                rise_interrupt_dwt: dwt_ref,
+                rise_interrupt_counter: counter_ref,
                 next_time: None,
-
-                isr_dwt: dwt_ref,
-                isr_switch_activation_count: 0,
-                switch_cycles: 0,
-                time_ns: 0.0, 
-                wc_isr_switch: 0.0,
-                isr_hclk_mhz: hclk_mhz,
     
                 // Delay_until
-                delay_until_dwt: dwt_ref,
+                delay_until_counter: counter_ref,
                 delay_until_hclk_mhz: hclk_mhz,
                 delay_until_activation_count: 0,
                 delay_interval: 10, 
                 delay_until_cycles: 0,
-                delay_until_overhead: 0.0,
-                wc_delay_until_overhead: 0.0,
+                delay_until_overhead: 0,
+                wc_delay_until_overhead: 0,
 
                 // Signal rtic_sync
                 signal_writer,
-                signal_writer_dwt: dwt_ref,
+                signal_writer_counter: counter_ref,
 
                 signal_reader,
-                signal_reader_dwt: dwt_ref,
+                signal_reader_counter: counter_ref,
                 signal_reader_cycles: 0,
-                signal_reader_time: 0.0,
-                wc_signal_rtic_sync: 0.0,
+                signal_reader_time: 0,
+                wc_signal_rtic_sync: 0,
                 signal_reader_hclk_mhz: hclk_mhz,
                 signal_reader_activation_count: 0,
 
                 // TaskSemaphore
                 task_semaphore_waiter,
-                task_semaphore_waiter_dwt: dwt_ref,
+                task_semaphore_waiter_counter: counter_ref,
 
                 task_semaphore_waiter_cycles: 0,
                 task_semaphore_waiter_hclk_mhz: hclk_mhz,
-                task_semaphore_waiter_time: 0.0,
-                wc_task_semaphore_waiter: 0.0,
+                task_semaphore_waiter_time: 0,
+                wc_task_semaphore_waiter: 0,
                 task_semaphore_waiter_activation_count: 0,
                 task_semaphore_signaler,
-                task_semaphore_signaler_dwt: dwt_ref,
+                task_semaphore_signaler_counter: counter_ref,
+                task_semaphore_completion,
+                wc_task_semaphore_completion: 0,
+                task_semaphore_watchdog,
 
                 // EventQueue
-                event_queue_waiter,
-                event_queue_waiter_dwt: dwt_ref,
-
-                event_queue_waiter_cycles: 0,
+                event_queue_waiters,
+                event_queue_waiter_counter: counter_ref,
                 event_queue_waiter_hclk_mhz: hclk_mhz,
-                event_queue_waiter_time: 0.0,
-                wc_event_queue_waiter: 0.0,
-                event_queue_waiter_activation_count: 0,
-                event_queue_signaler,
-                event_queue_signaler_dwt: dwt_ref,
+                event_queue_signalers,
+                event_queue_signaler_counter: counter_ref,
+                event_queue_signaler_hclk_mhz: hclk_mhz,
+
+                // Timed wait
+                timed_wait_waiter,
+                timed_wait_waiter_counter: counter_ref,
+                timed_wait_waiter_hclk_mhz: hclk_mhz,
+                timed_wait_signaler,
+                timed_wait_signaler_counter: counter_ref,
+
+                // MIT enforcement
+                mit_waiter,
+                mit_waiter_counter: counter_ref,
+                mit_waiter_hclk_mhz: hclk_mhz,
+                mit_signaler,
+                mit_signaler_counter: counter_ref,
+                mit_signaler_hclk_mhz: hclk_mhz,
+                deferred_wait_waiter,
+                deferred_wait_signaler,
+
+                // ProtectedEntry
+                protected_entry_caller,
+                protected_entry_caller_counter: counter_ref,
+                protected_entry_caller_hclk_mhz: hclk_mhz,
+                protected_procedure,
+                protected_procedure_counter: counter_ref,
+                protected_procedure_hclk_mhz: hclk_mhz,
+
+                // Broadcast
+                #[cfg(feature = "broadcast")]
+                broadcast_signaler,
+
+                // Lock-free signal
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_semaphore_waiter,
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_semaphore_signaler,
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_lock_free_waiter,
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_lock_free_signaler,
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_queue_waiter,
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_queue_signaler,
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_lock_free_queue_waiter,
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_lock_free_queue_signaler,
+                #[cfg(feature = "lock-free-signal")]
+                signal_cost_lock_free_watchdogs: [
+                    signal_cost_lock_free_activation_reader,
+                    signal_cost_lock_free_queue_activation_reader,
+                ],
+
+                // Activation overrun
+                #[cfg(feature = "activation-overrun")]
+                overrun_semaphore_waiter,
+                #[cfg(feature = "activation-overrun")]
+                overrun_semaphore_signaler,
+                #[cfg(feature = "activation-overrun")]
+                overrun_queue_waiter,
+                #[cfg(feature = "activation-overrun")]
+                overrun_queue_signaler,
 
                 // Spawn overhead
-                spawn_overhead_dwt: dwt_ref, 
+                spawn_overhead_counter: counter_ref, 
                 spawn_overhead_cycles: 0, 
-                spawn_overhead_ns: 0.0, 
-                bc_spawn_overhead: f32::MAX, 
+                spawn_overhead_ns: 0, 
+                bc_spawn_overhead: u32::MAX, 
                 spawn_overhead_hclk_mhz: hclk_mhz, 
                 spawn_overhead_activation_count: 0,
 
                 // Context switch
-                context_switch_task_dwt: dwt_ref, 
+                context_switch_task_counter: counter_ref, 
                 context_switch_task_cycles: 0, 
                 context_switch_task_hclk_mhz: hclk_mhz, 
-                context_switch_ns: 0.0, 
-                wc_context_switch: 0.0, 
+                context_switch_ns: 0, 
+                wc_context_switch: 0, 
                 context_switch_task_activation_count: 0,
             }
         )
     }
 
-    #[task(priority = 1, local=[rise_interrupt_dwt, next_time])]
+    #[task(priority = 1, local=[rise_interrupt_counter, next_time])]
     async fn rise_interrupt(cx: rise_interrupt::Context) -> ! {
         defmt::info!("Start of isr-switch profiling.");
-        unsafe { NVIC::unmask(interrupt::EXTI0) };
+        unsafe { NVIC::unmask(board::ISR_SWITCH_IRQ) };
         loop {
-            *cx.local.next_time = Some(Mono::now() + (1 as u32).secs());
+            *cx.local.next_time = Some(Mono::now() + Duration::secs(1));
             
             critical_section::with(|_cs| {
-                NVIC::pend(interrupt::EXTI0);
-                unsafe{ cx.local.rise_interrupt_dwt.cyccnt.write(0) };  
+                NVIC::pend(board::ISR_SWITCH_IRQ);
+                cx.local.rise_interrupt_counter.reset();  
             });
 
             Mono::delay_until(cx.local.next_time.unwrap()).await;
@@ -335,35 +776,119 @@ mod app {
     }
     
 
-    #[task(binds = EXTI0, local = [isr_dwt, isr_switch_activation_count, switch_cycles, isr_hclk_mhz, time_ns, wc_isr_switch])]
-    fn exti0_isr(cx: exti0_isr::Context) {        
-        *cx.local.switch_cycles = cx.local.isr_dwt.cyccnt.read();
-        *cx.local.time_ns = (*cx.local.switch_cycles as f32 / *cx.local.isr_hclk_mhz) * 1000.0;
-        defmt::info!("ISR switch time: {} ns (number of cycles: {})", *cx.local.time_ns as u32, *cx.local.switch_cycles);
+    // The ISR-Switch interrupt differs between boards, hence a hardware task per board sharing the same body
+    #[cfg(feature = "olimex-h405")]
+    #[task(binds = EXTI0, local = [isr_switch_activation_count: u32 = 0, wc_isr_switch: u32 = 0])]
+    fn exti0_isr(cx: exti0_isr::Context) {
+        isr_switch(cx.local.isr_switch_activation_count, cx.local.wc_isr_switch);
+    }
+
+    #[cfg(feature = "lm3s6965evb")]
+    #[task(binds = GPIOA, local = [isr_switch_activation_count: u32 = 0, wc_isr_switch: u32 = 0])]
+    fn gpioa_isr(cx: gpioa_isr::Context) {
+        isr_switch(cx.local.isr_switch_activation_count, cx.local.wc_isr_switch);
+    }
+
+    #[cfg(feature = "microbit")]
+    #[task(binds = SWI2, local = [isr_switch_activation_count: u32 = 0, wc_isr_switch: u32 = 0])]
+    fn swi2_isr(cx: swi2_isr::Context) {
+        isr_switch(cx.local.isr_switch_activation_count, cx.local.wc_isr_switch);
+    }
+
+    #[cfg(feature = "mps2-an505")]
+    #[task(binds = UART2RX, local = [isr_switch_activation_count: u32 = 0, wc_isr_switch: u32 = 0])]
+    fn uart2rx_isr(cx: uart2rx_isr::Context) {
+        isr_switch(cx.local.isr_switch_activation_count, cx.local.wc_isr_switch);
+    }
+
+    #[inline(always)]
+    fn isr_switch(isr_switch_activation_count: &mut u32, wc_isr_switch: &mut u32) {
+        let switch_cycles = get_cycle_counter().read();
+        let time_ns = cycles_to_ns(switch_cycles, get_hclk_mhz());
+        defmt::info!("ISR switch time: {} ns (number of cycles: {})", time_ns, switch_cycles);
         defmt::info!("--------------------------------------------");
 
         // Update the wc_isr_switch
-        *cx.local.wc_isr_switch = (*cx.local.wc_isr_switch).max(*cx.local.time_ns);
+        *wc_isr_switch = (*wc_isr_switch).max(time_ns);
+
+        *isr_switch_activation_count += 1;
+        if *isr_switch_activation_count == WCET_THRESHOLD {
+            defmt::info!("WC ISR switch time: {} ns", *wc_isr_switch);
+            if end_of_window(isr_switch_activation_count, "End of isr-switch profiling.") {
+                *wc_isr_switch = 0;
+            }
+        }
+    }
+
+    // Keeps the timer queue busy, so that the monotonic interrupts release a task every other millisecond
+    #[cfg(overhead_mono)]
+    #[task(priority = 1)]
+    async fn timer_queue_profiling(_cx: timer_queue_profiling::Context) -> ! {
+        defmt::info!("Start of timer queue profiling.");
+        let mut activations: u32 = 0;
+        loop {
+            Mono::delay(Duration::millis(1)).await;
+
+            // Progress of the monotonic profiling, read from its profiling state
+            activations += 1;
+            if activations % WCET_THRESHOLD == 0 {
+                let snapshot = Mono::profile().snapshot();
+                let wc_handler = snapshot.wakes.buckets().iter().map(|stats| stats.wc_ns).max().unwrap_or(0);
+                defmt::info!("Monotonic profiling: {} samples, worst-case handler {} ns", snapshot.samples, wc_handler);
+            }
+        }
+    }
 
-        *cx.local.isr_switch_activation_count += 1;
-        if *cx.local.isr_switch_activation_count == WCET_THRESHOLD {
-            defmt::info!("WC ISR switch time: {} ns", *cx.local.wc_isr_switch as u32);
-            defmt::panic!("End of isr-switch profiling.");
+    // Checks that the tickless monotonic keeps time while its interrupt is delayed by more than a tick
+    #[cfg(feature = "tickless-drift")]
+    #[task(priority = 1, local = [tickless_drift_activation_count: u32 = 0, wc_drift: u32 = 0])]
+    async fn tickless_drift_profiling(cx: tickless_drift_profiling::Context) -> ! {
+        let counter = get_cycle_counter();
+        let cycles_per_ms = get_hclk_mhz() * 1_000;
+        defmt::info!("Start of tickless drift profiling.");
+        loop {
+            // Both clocks read right after a tick, for the drift not to include the tick quantization
+            Mono::delay(Duration::millis(1)).await;
+            let start = Mono::now();
+            let start_cycles = counter.read();
+
+            for masked_ticks in 1..=5 {
+                Mono::delay(Duration::millis(2)).await;
+                // The SysTick interrupt stays pending while masked, past several short periods
+                cortex_m::interrupt::free(|_| cortex_m::asm::delay(masked_ticks * cycles_per_ms));
+            }
+
+            Mono::delay(Duration::millis(1)).await;
+            let elapsed_ms = (Mono::now() - start).to_millis() as u32;
+            let counted_ms = counter.read().wrapping_sub(start_cycles) / cycles_per_ms;
+            let drift = elapsed_ms.abs_diff(counted_ms);
+            defmt::info!("Tickless drift: {} ms over {} ms", drift, counted_ms);
+            defmt::info!("--------------------------------------------");
+
+            *cx.local.wc_drift = (*cx.local.wc_drift).max(drift);
+
+            *cx.local.tickless_drift_activation_count += 1;
+            if *cx.local.tickless_drift_activation_count == WCET_THRESHOLD {
+                defmt::info!("WC tickless drift: {} ms", *cx.local.wc_drift);
+                if end_of_window(cx.local.tickless_drift_activation_count, "End of tickless drift profiling.") {
+                    *cx.local.wc_drift = 0;
+                }
+            }
         }
-    } 
+    }
 
-    #[task(priority = 1, local =[delay_until_dwt, delay_until_hclk_mhz, delay_until_activation_count, delay_interval, delay_until_cycles, delay_until_overhead, wc_delay_until_overhead])]
+    #[task(priority = 1, local =[delay_until_counter, delay_until_hclk_mhz, delay_until_activation_count, delay_interval, delay_until_cycles, delay_until_overhead, wc_delay_until_overhead])]
     async fn delay_until_profiling(cx: delay_until_profiling::Context) -> ! {
         loop {
-            unsafe { cx.local.delay_until_dwt.cyccnt.write(0) };
-            Mono::delay_until(Mono::now() + cx.local.delay_interval.nanos()).await;
-            *cx.local.delay_until_cycles = cx.local.delay_until_dwt.cyccnt.read();
+            cx.local.delay_until_counter.reset();
+            Mono::delay_until(Mono::now() + Duration::nanos((*cx.local.delay_interval).into())).await;
+            *cx.local.delay_until_cycles = cx.local.delay_until_counter.read();
 
             *cx.local.delay_until_overhead = 
-                (*cx.local.delay_until_cycles as f32 /  *cx.local.delay_until_hclk_mhz) * 1000.0 // tot delay_until time in ns
-                - (*cx.local.delay_interval as f32);                                             // - delay interval in ns = overhead 
+                cycles_to_ns(*cx.local.delay_until_cycles, *cx.local.delay_until_hclk_mhz) // tot delay_until time in ns
+                .saturating_sub(*cx.local.delay_interval);                                  // - delay interval in ns = overhead 
 
-            defmt::info!("Delay_until overhead: {} ns", *cx.local.delay_until_overhead as u32);
+            defmt::info!("Delay_until overhead: {} ns", *cx.local.delay_until_overhead);
             defmt::info!("--------------------------------------------");
 
             // Update the wc_delay_until_overhead
@@ -371,32 +896,132 @@ mod app {
 
             *cx.local.delay_until_activation_count += 1;
             if *cx.local.delay_until_activation_count == WCET_THRESHOLD {
-                defmt::info!("WC Delay_until overhead: {} ns", *cx.local.wc_delay_until_overhead as u32);
-                defmt::panic!("End of delay until profiling.");
+                defmt::info!("WC Delay_until overhead: {} ns", *cx.local.wc_delay_until_overhead);
+                if end_of_window(cx.local.delay_until_activation_count, "End of delay until profiling.") {
+                    *cx.local.wc_delay_until_overhead = 0;
+                }
             }            
         }
     }
 
-    #[task(priority = 2, local = [signal_writer, signal_writer_dwt])]
+    // Splits the lateness of each wake-up into tick quantization, interrupt and dispatch components
+    #[cfg(feature = "wake-lateness")]
+    #[task(priority = 1, local = [
+        wake_lateness_activation_count: u32 = 0,
+        request_phase: u32 = 0,
+        wc_quantization: u32 = 0,
+        wc_interrupt: u32 = 0,
+        wc_dispatch: u32 = 0,
+    ])]
+    async fn wake_lateness_profiling(cx: wake_lateness_profiling::Context) -> ! {
+        let counter = get_cycle_counter();
+        let hclk_mhz = get_hclk_mhz();
+        let delay_interval = Duration::millis(1);
+        let delay_interval_cycles = hclk_mhz * 1_000;
+        defmt::info!("Start of wake lateness profiling.");
+        loop {
+            // Sweep the request over the tick period
+            *cx.local.request_phase = (*cx.local.request_phase + 7_919) % delay_interval_cycles;
+            cortex_m::asm::delay(*cx.local.request_phase);
+
+            let request_cycles = counter.read();
+            let requested: Instant = Mono::now() + delay_interval + Duration::from_ticks(1);
+            Mono::delay(delay_interval).await;
+            let resume_cycles = counter.read();
+
+            let Some(release) = crate::wake_lateness::take_release() else {
+                defmt::warn!("Wake-up not released by the SysTick interrupt, skipped");
+                continue;
+            };
+            if release.tick != requested {
+                defmt::warn!("Requested tick {}, released at tick {}", requested.ticks(), release.tick.ticks());
+            }
+
+            // Ideal wake-up: exactly the delay interval after the request
+            let ideal_cycles = request_cycles.wrapping_add(delay_interval_cycles);
+            let quantization = cycles_to_ns((release.tick_cycles.wrapping_sub(ideal_cycles) as i32).max(0) as u32, hclk_mhz);
+            let interrupt = cycles_to_ns(release.release_cycles.wrapping_sub(release.tick_cycles), hclk_mhz);
+            let dispatch = cycles_to_ns(resume_cycles.wrapping_sub(release.release_cycles), hclk_mhz);
+
+            defmt::info!(
+                "Wake lateness: quantization {} ns, interrupt {} ns, dispatch {} ns",
+                quantization, interrupt, dispatch,
+            );
+            defmt::info!("--------------------------------------------");
+
+            // Update the worst cases
+            *cx.local.wc_quantization = (*cx.local.wc_quantization).max(quantization);
+            *cx.local.wc_interrupt = (*cx.local.wc_interrupt).max(interrupt);
+            *cx.local.wc_dispatch = (*cx.local.wc_dispatch).max(dispatch);
+
+            *cx.local.wake_lateness_activation_count += 1;
+            if *cx.local.wake_lateness_activation_count == WCET_THRESHOLD {
+                defmt::info!(
+                    "WC wake lateness: quantization {} ns, interrupt {} ns, dispatch {} ns",
+                    *cx.local.wc_quantization, *cx.local.wc_interrupt, *cx.local.wc_dispatch,
+                );
+                if end_of_window(cx.local.wake_lateness_activation_count, "End of wake lateness profiling.") {
+                    *cx.local.wc_quantization = 0;
+                    *cx.local.wc_interrupt = 0;
+                    *cx.local.wc_dispatch = 0;
+                }
+            }
+        }
+    }
+
+    // Measures the cost of Mono::now() and of the SysTick handler for the tick counter width of
+    // this build, see `scripts/systick-width.sh` for both widths
+    #[cfg(feature = "systick-width")]
+    #[task(priority = 1, local = [systick_width_activation_count: u32 = 0])]
+    async fn systick_width_profiling(cx: systick_width_profiling::Context) -> ! {
+        let hclk_mhz = get_hclk_mhz();
+        let bits = crate::systick_width::COUNTER_BITS;
+        defmt::info!("Start of SysTick counter width profiling ({}-bit counter).", bits);
+        // Discard the interrupts preceding the first window
+        crate::systick_width::reset_width_costs();
+        loop {
+            Mono::delay(Duration::millis(1)).await;
+            crate::systick_width::measure_now();
+
+            *cx.local.systick_width_activation_count += 1;
+            if *cx.local.systick_width_activation_count == WCET_THRESHOLD {
+                let costs = crate::systick_width::width_costs();
+                defmt::info!(
+                    "SysTick {}-bit counter: now() worst-case {} ns, mean {} ns; handler worst-case {} ns, mean {} ns ({} interrupts)",
+                    bits,
+                    cycles_to_ns(costs.now.wc_cycles, hclk_mhz),
+                    cycles_to_ns(costs.now.mean_cycles(), hclk_mhz),
+                    cycles_to_ns(costs.handler.wc_cycles, hclk_mhz),
+                    cycles_to_ns(costs.handler.mean_cycles(), hclk_mhz),
+                    costs.handler.count,
+                );
+                if end_of_window(cx.local.systick_width_activation_count, "End of SysTick width profiling.") {
+                    crate::systick_width::reset_width_costs();
+                }
+            }
+        }
+    }
+
+    #[task(priority = 2, local = [signal_writer, signal_writer_counter])]
     async fn signal_writer_task(cx: signal_writer_task::Context) -> ! {
         loop {
             critical_section::with( |_cs| {
                 cx.local.signal_writer.write(());
-                unsafe{ cx.local.signal_writer_dwt.cyccnt.write(0) };
+                cx.local.signal_writer_counter.reset();
             });
 
-            Mono::delay((1 as u32).secs()).await;
+            Mono::delay(Duration::secs(1)).await;
         }
     }
 
-    #[task(priority = 1, local = [signal_reader, signal_reader_dwt, signal_reader_cycles, signal_reader_hclk_mhz, signal_reader_time, wc_signal_rtic_sync, signal_reader_activation_count])]
+    #[task(priority = 1, local = [signal_reader, signal_reader_counter, signal_reader_cycles, signal_reader_hclk_mhz, signal_reader_time, wc_signal_rtic_sync, signal_reader_activation_count])]
     async fn signal_reader_task(cx: signal_reader_task::Context) -> ! {
         loop {
             cx.local.signal_reader.wait().await;
-            *cx.local.signal_reader_cycles = cx.local.signal_reader_dwt.cyccnt.read();
+            *cx.local.signal_reader_cycles = cx.local.signal_reader_counter.read();
 
-            *cx.local.signal_reader_time = (*cx.local.signal_reader_cycles as f32 /  *cx.local.signal_reader_hclk_mhz) * 1000.0;
-            defmt::info!("Signal RTIC sync time: {} ns (number of cycles: {})", *cx.local.signal_reader_time as u32, *cx.local.signal_reader_cycles);
+            *cx.local.signal_reader_time = cycles_to_ns(*cx.local.signal_reader_cycles, *cx.local.signal_reader_hclk_mhz);
+            defmt::info!("Signal RTIC sync time: {} ns (number of cycles: {})", *cx.local.signal_reader_time, *cx.local.signal_reader_cycles);
             defmt::info!("---------------------------------------------------");
 
             // Update the wc_signal_rtic_sync
@@ -404,87 +1029,774 @@ mod app {
 
             *cx.local.signal_reader_activation_count += 1;
             if *cx.local.signal_reader_activation_count == WCET_THRESHOLD {
-                defmt::info!("WC signal RTIC sync time: {} ns", *cx.local.wc_signal_rtic_sync as u32);
-                defmt::panic!("End of signal rttc_sync profiling.");
+                defmt::info!("WC signal RTIC sync time: {} ns", *cx.local.wc_signal_rtic_sync);
+                if end_of_window(cx.local.signal_reader_activation_count, "End of signal rttc_sync profiling.") {
+                    *cx.local.wc_signal_rtic_sync = 0;
+                }
             }
         }
     }
 
-    #[task(priority =2, local = [task_semaphore_signaler, task_semaphore_signaler_dwt])]
+    #[task(priority =2, local = [task_semaphore_signaler, task_semaphore_signaler_counter])]
     async fn task_seamaphore_signaler_task(cx: task_seamaphore_signaler_task::Context) -> ! {
         loop {
-            critical_section::with( |_cs| {
-                cx.local.task_semaphore_signaler.signal();
-                unsafe{ cx.local.task_semaphore_signaler_dwt.cyccnt.write(0) };
-            });
+            // The waiter measures its wake-up from the last activation of the burst
+            for _ in 0..TASK_SEMAPHORE_BURST {
+                critical_section::with( |_cs| {
+                    defmt::unwrap!(cx.local.task_semaphore_signaler.signal());
+                    cx.local.task_semaphore_signaler_counter.reset();
+                });
+            }
 
-            Mono::delay((1 as u32).secs()).await;
+            Mono::delay(Duration::secs(1)).await;
         }
     }
 
-    #[task(priority = 1, local = [task_semaphore_waiter, task_semaphore_waiter_dwt, task_semaphore_waiter_cycles, task_semaphore_waiter_hclk_mhz, task_semaphore_waiter_time, wc_task_semaphore_waiter, task_semaphore_waiter_activation_count])]
+    #[task(priority = 1, local = [task_semaphore_waiter, task_semaphore_waiter_counter, task_semaphore_waiter_cycles, task_semaphore_waiter_hclk_mhz, task_semaphore_waiter_time, wc_task_semaphore_waiter, task_semaphore_waiter_activation_count, task_semaphore_completion, wc_task_semaphore_completion, task_semaphore_dropped: u32 = 0])]
     async fn task_semaphore_waiter_task(cx: task_semaphore_waiter_task::Context) -> ! {
         loop {
             cx.local.task_semaphore_waiter.wait().await;
-            *cx.local.task_semaphore_waiter_cycles = cx.local.task_semaphore_waiter_dwt.cyccnt.read();
+            *cx.local.task_semaphore_waiter_cycles = cx.local.task_semaphore_waiter_counter.read();
 
-            *cx.local.task_semaphore_waiter_time = (*cx.local.task_semaphore_waiter_cycles as f32 /  *cx.local.task_semaphore_waiter_hclk_mhz) * 1000.0;
-            defmt::info!("Task Semaphore wait time: {} ns (number of cycles: {})", *cx.local.task_semaphore_waiter_time as u32, *cx.local.task_semaphore_waiter_cycles);
+            *cx.local.task_semaphore_waiter_time = cycles_to_ns(*cx.local.task_semaphore_waiter_cycles, *cx.local.task_semaphore_waiter_hclk_mhz);
+            defmt::info!("Task Semaphore wait time: {} ns (number of cycles: {})", *cx.local.task_semaphore_waiter_time, *cx.local.task_semaphore_waiter_cycles);
             defmt::info!("---------------------------------------------------");
 
             // Update the wc_task_semaphore_waiter
             *cx.local.wc_task_semaphore_waiter = (*cx.local.wc_task_semaphore_waiter).max(*cx.local.task_semaphore_waiter_time);
 
+            // Counting mode: the burst left the next releases pending, up to the maximum count
+            #[cfg(feature = "task-semaphore-counting")]
+            {
+                for _ in 1..TASK_SEMAPHORE_MAX_COUNT {
+                    cx.local.task_semaphore_waiter.wait().await;
+                }
+                let dropped = cx.local.task_semaphore_waiter.dropped();
+                let burst_dropped = dropped - *cx.local.task_semaphore_dropped;
+                *cx.local.task_semaphore_dropped = dropped;
+                if burst_dropped != TASK_SEMAPHORE_BURST - TASK_SEMAPHORE_MAX_COUNT {
+                    defmt::warn!("Task semaphore burst of {}: {} releases dropped", TASK_SEMAPHORE_BURST, burst_dropped);
+                }
+            }
+
+            // Overrun the deadline every DEADLINE_OVERRUN_PERIOD activations
+            if cfg!(feature = "deadline-watchdog") && *cx.local.task_semaphore_waiter_activation_count % DEADLINE_OVERRUN_PERIOD == DEADLINE_OVERRUN_PERIOD - 1 {
+                cortex_m::asm::delay(150 * 1_000 * *cx.local.task_semaphore_waiter_hclk_mhz);
+            }
+
+            // Report the completion, the deadline watchdog preempts the waiter to disarm
+            let completion_start = cx.local.task_semaphore_waiter_counter.read();
+            cx.local.task_semaphore_completion.complete();
+            let completion_cycles = cx.local.task_semaphore_waiter_counter.read().wrapping_sub(completion_start);
+            *cx.local.wc_task_semaphore_completion = (*cx.local.wc_task_semaphore_completion).max(cycles_to_ns(completion_cycles, *cx.local.task_semaphore_waiter_hclk_mhz));
+
             *cx.local.task_semaphore_waiter_activation_count += 1;
             if *cx.local.task_semaphore_waiter_activation_count == WCET_THRESHOLD {
-                defmt::info!("WC task semaphore wait time: {} ns", *cx.local.wc_task_semaphore_waiter as u32);
-                defmt::panic!("End of task semaphore waiter profiling.");
+                defmt::info!("WC task semaphore wait time: {} ns", *cx.local.wc_task_semaphore_waiter);
+                #[cfg(feature = "deadline-watchdog")]
+                defmt::info!("WC deadline watchdog disarm time: {} ns", *cx.local.wc_task_semaphore_completion);
+                let reset = if cfg!(feature = "deadline-watchdog") {
+                    // The deadline watchdog task ends the window, reporting after this one
+                    *cx.local.task_semaphore_waiter_activation_count = 0;
+                    cfg!(feature = "continuous-reset")
+                } else {
+                    end_of_window(cx.local.task_semaphore_waiter_activation_count, "End of task semaphore waiter profiling.")
+                };
+                if reset {
+                    *cx.local.wc_task_semaphore_waiter = 0;
+                    *cx.local.wc_task_semaphore_completion = 0;
+                }
             }
         }
     }
 
-    #[task(priority =2, local = [event_queue_signaler, event_queue_signaler_dwt])]
-    async fn event_queue_signaler_task(cx: event_queue_signaler_task::Context) -> ! {
+    // Watches the relative deadline of the task semaphore waiter
+    #[task(priority = 2, local = [task_semaphore_watchdog, deadline_watchdog_activation_count: u32 = 0])]
+    async fn deadline_watchdog_task(cx: deadline_watchdog_task::Context) -> ! {
+        let watchdog = cx.local.task_semaphore_watchdog;
         loop {
-            critical_section::with( |_cs| {
-                cx.local.event_queue_signaler.signal(());
-                unsafe{ cx.local.event_queue_signaler_dwt.cyccnt.write(0) };
-            });
+            let activation = watchdog.arm().await;
+
+            // The window ends with the activation following its last one: the waiter reports
+            // its own window once the last one completes, which this task preempts
+            if *cx.local.deadline_watchdog_activation_count == WCET_THRESHOLD {
+                let stats = watchdog.stats();
+                defmt::info!(
+                    "Deadline watchdog: {} activations, {} misses, WC lateness {} us",
+                    stats.activations, stats.misses, stats.wc_lateness_us,
+                );
+                if end_of_window(cx.local.deadline_watchdog_activation_count, "End of deadline watchdog profiling.") {
+                    watchdog.reset_stats();
+                }
+            }
+
+            if let Err(miss) = watchdog.watch(activation).await {
+                defmt::warn!(
+                    "Deadline miss of the activation at {} us, lateness {} us",
+                    miss.activation.duration_since_epoch().to_micros(), miss.lateness.to_micros(),
+                );
+            }
+            *cx.local.deadline_watchdog_activation_count += 1;
+        }
+    }
 
-            Mono::delay((1 as u32).secs()).await;
+    // Sweeps the payload sizes and the queue occupancies, in lockstep with the waiter task
+    #[task(priority = 2, local = [
+        event_queue_signalers,
+        event_queue_signaler_counter,
+        event_queue_signaler_hclk_mhz,
+        event_queue_sweep_count: u32 = 0,
+        wc_event_queue_signal: [[u32; EVENT_QUEUE_CAPACITY]; 3] = [[0; EVENT_QUEUE_CAPACITY]; 3],
+    ])]
+    async fn event_queue_signaler_task(cx: event_queue_signaler_task::Context) -> ! {
+        let counter = *cx.local.event_queue_signaler_counter;
+        let hclk_mhz = *cx.local.event_queue_signaler_hclk_mhz;
+        let (small, medium, large) = cx.local.event_queue_signalers;
+        let wc = cx.local.wc_event_queue_signal;
+        loop {
+            for occupancy in 0..EVENT_QUEUE_CAPACITY {
+                let cycles = signal_event(small, counter, [0; 4], occupancy);
+                wc[0][occupancy] = wc[0][occupancy].max(cycles_to_ns(cycles, hclk_mhz));
+                Mono::delay(Duration::millis(10)).await;
+
+                let cycles = signal_event(medium, counter, [0; 32], occupancy);
+                wc[1][occupancy] = wc[1][occupancy].max(cycles_to_ns(cycles, hclk_mhz));
+                Mono::delay(Duration::millis(10)).await;
+
+                let cycles = signal_event(large, counter, [0; 128], occupancy);
+                wc[2][occupancy] = wc[2][occupancy].max(cycles_to_ns(cycles, hclk_mhz));
+
+                // The waiter task reports the last sweep, and ends the window, during this delay
+                if occupancy == EVENT_QUEUE_CAPACITY - 1 {
+                    *cx.local.event_queue_sweep_count += 1;
+                    if *cx.local.event_queue_sweep_count == WCET_THRESHOLD {
+                        for (size, wc) in [4, 32, 128].into_iter().zip(wc.iter_mut()) {
+                            defmt::info!("WC event queue signal time, {} byte payload, by occupancy: {} ns", size, *wc);
+                            if cfg!(feature = "continuous-reset") {
+                                *wc = [0; EVENT_QUEUE_CAPACITY];
+                            }
+                        }
+                        *cx.local.event_queue_sweep_count = 0;
+                    }
+                }
+                Mono::delay(Duration::millis(10)).await;
+            }
         }
     }
 
-    #[task(priority = 1, local = [event_queue_waiter, event_queue_waiter_dwt, event_queue_waiter_cycles, event_queue_waiter_hclk_mhz, event_queue_waiter_time, wc_event_queue_waiter, event_queue_waiter_activation_count])]
+    #[task(priority = 1, local = [
+        event_queue_waiters,
+        event_queue_waiter_counter,
+        event_queue_waiter_hclk_mhz,
+        event_queue_waiter_activation_count: u32 = 0,
+        wc_event_queue_wait: [[u32; EVENT_QUEUE_CAPACITY]; 3] = [[0; EVENT_QUEUE_CAPACITY]; 3],
+    ])]
     async fn event_queue_waiter_task(cx: event_queue_waiter_task::Context) -> ! {
+        let counter = *cx.local.event_queue_waiter_counter;
+        let hclk_mhz = *cx.local.event_queue_waiter_hclk_mhz;
+        let (small, medium, large) = cx.local.event_queue_waiters;
+        let wc = cx.local.wc_event_queue_wait;
         loop {
-            cx.local.event_queue_waiter.wait().await;
-            *cx.local.event_queue_waiter_cycles = cx.local.event_queue_waiter_dwt.cyccnt.read();
+            for occupancy in 0..EVENT_QUEUE_CAPACITY {
+                let cycles = wait_event(small, counter).await;
+                wc[0][occupancy] = wc[0][occupancy].max(cycles_to_ns(cycles, hclk_mhz));
 
-            *cx.local.event_queue_waiter_time = (*cx.local.event_queue_waiter_cycles as f32 /  *cx.local.event_queue_waiter_hclk_mhz) * 1000.0;
-            defmt::info!("Event Queue wait time: {} ns (number of cycles: {})", *cx.local.event_queue_waiter_time as u32, *cx.local.event_queue_waiter_cycles);
-            defmt::info!("---------------------------------------------------");
+                let cycles = wait_event(medium, counter).await;
+                wc[1][occupancy] = wc[1][occupancy].max(cycles_to_ns(cycles, hclk_mhz));
 
-            // Update the wc_event_queue_waiter
-            *cx.local.wc_event_queue_waiter = (*cx.local.wc_event_queue_waiter).max(*cx.local.event_queue_waiter_time);
+                let cycles = wait_event(large, counter).await;
+                wc[2][occupancy] = wc[2][occupancy].max(cycles_to_ns(cycles, hclk_mhz));
+            }
 
             *cx.local.event_queue_waiter_activation_count += 1;
             if *cx.local.event_queue_waiter_activation_count == WCET_THRESHOLD {
-                defmt::info!("WC event queue wait time: {} ns", *cx.local.wc_event_queue_waiter as u32);
-                defmt::panic!("End of event queue waiter profiling.");
+                for (size, wc) in [4, 32, 128].into_iter().zip(wc.iter()) {
+                    defmt::info!("WC event queue wait time, {} byte payload, by occupancy: {} ns", size, *wc);
+                }
+                if end_of_window(cx.local.event_queue_waiter_activation_count, "End of event queue profiling.") {
+                    *wc = [[0; EVENT_QUEUE_CAPACITY]; 3];
+                }
+            }
+        }
+    }
+
+    #[task(priority = 2, local = [timed_wait_signaler, timed_wait_signaler_counter])]
+    async fn timed_wait_signaler_task(cx: timed_wait_signaler_task::Context) -> ! {
+        loop {
+            critical_section::with(|_cs| {
+                defmt::unwrap!(cx.local.timed_wait_signaler.signal());
+                cx.local.timed_wait_signaler_counter.reset();
+            });
+
+            Mono::delay(Duration::secs(1)).await;
+        }
+    }
+
+    // Alternates plain and timed waits on the same semaphore: the release of a timed wait also
+    // removes its timeout from the timer queue
+    #[task(priority = 1, local = [
+        timed_wait_waiter,
+        timed_wait_waiter_counter,
+        timed_wait_waiter_hclk_mhz,
+        timed_wait_activation_count: u32 = 0,
+        wc_plain_wait: u32 = 0,
+        wc_timed_wait: u32 = 0,
+    ])]
+    async fn timed_wait_waiter_task(cx: timed_wait_waiter_task::Context) -> ! {
+        let counter = *cx.local.timed_wait_waiter_counter;
+        let hclk_mhz = *cx.local.timed_wait_waiter_hclk_mhz;
+        loop {
+            cx.local.timed_wait_waiter.wait().await;
+            let plain_wait = cycles_to_ns(counter.read(), hclk_mhz);
+
+            // The timeout never expires, the signal always wins
+            if cx.local.timed_wait_waiter.wait_timeout(Duration::secs(2)).await.is_err() {
+                defmt::panic!("Timed wait expired before the signal");
+            }
+            let timed_wait = cycles_to_ns(counter.read(), hclk_mhz);
+
+            defmt::info!("Wait time: plain {} ns, timed {} ns", plain_wait, timed_wait);
+            defmt::info!("---------------------------------------------------");
+
+            *cx.local.wc_plain_wait = (*cx.local.wc_plain_wait).max(plain_wait);
+            *cx.local.wc_timed_wait = (*cx.local.wc_timed_wait).max(timed_wait);
+
+            *cx.local.timed_wait_activation_count += 1;
+            if *cx.local.timed_wait_activation_count == WCET_THRESHOLD {
+                defmt::info!(
+                    "WC wait time: plain {} ns, timed {} ns (added {} ns)",
+                    *cx.local.wc_plain_wait,
+                    *cx.local.wc_timed_wait,
+                    (*cx.local.wc_timed_wait).saturating_sub(*cx.local.wc_plain_wait),
+                );
+                if end_of_window(cx.local.timed_wait_activation_count, "End of timed wait profiling.") {
+                    *cx.local.wc_plain_wait = 0;
+                    *cx.local.wc_timed_wait = 0;
+                }
+            }
+        }
+    }
+
+    // Signals every 10 ms, every fourth activation only 2 ms after the previous one, against a
+    // 5 ms MIT: each window checks the activations with the next policy
+    #[task(priority = 2, shared = [mit_releases], local = [
+        mit_signaler,
+        mit_signaler_counter,
+        mit_signaler_hclk_mhz,
+        mit_activation_count: u32 = 0,
+        wc_mit_signal: u32 = 0,
+    ])]
+    async fn mit_signaler_task(mut cx: mit_signaler_task::Context) -> ! {
+        let counter = *cx.local.mit_signaler_counter;
+        let hclk_mhz = *cx.local.mit_signaler_hclk_mhz;
+        let mut policy = MitPolicy::Reject;
+        cx.local.mit_signaler.set_min_inter_arrival(Duration::millis(5), policy);
+        loop {
+            let signal_cycles = critical_section::with(|_cs| {
+                counter.reset();
+                // The rejected activations are counted in the MIT stats
+                let _ = cx.local.mit_signaler.signal();
+                let cycles = counter.read();
+                counter.reset();
+                cycles
+            });
+            *cx.local.wc_mit_signal = (*cx.local.wc_mit_signal).max(cycles_to_ns(signal_cycles, hclk_mhz));
+
+            *cx.local.mit_activation_count += 1;
+            if *cx.local.mit_activation_count == WCET_THRESHOLD {
+                let stats = defmt::unwrap!(cx.local.mit_signaler.mit_stats());
+                let (releases, wc_release) = cx.shared.mit_releases.lock(core::mem::take);
+                defmt::info!(
+                    "MIT {} policy: {} activations, {} violations, min separation {} us, {} releases, WC release latency {} ns, WC signal time {} ns",
+                    policy, stats.activations, stats.violations, stats.min_separation_us, releases, wc_release, *cx.local.wc_mit_signal,
+                );
+                *cx.local.wc_mit_signal = 0;
+                if policy == MitPolicy::Count {
+                    end_of_window(cx.local.mit_activation_count, "End of MIT enforcement profiling.");
+                } else {
+                    *cx.local.mit_activation_count = 0;
+                }
+
+                policy = match policy {
+                    MitPolicy::Reject => MitPolicy::Defer,
+                    MitPolicy::Defer => MitPolicy::Count,
+                    MitPolicy::Count => MitPolicy::Reject,
+                };
+                cx.local.mit_signaler.set_min_inter_arrival(Duration::millis(5), policy);
+            }
+
+            let early = *cx.local.mit_activation_count % 4 == 3;
+            Mono::delay(if early { Duration::millis(2) } else { Duration::millis(10) }).await;
+        }
+    }
+
+    #[task(priority = 1, shared = [mit_releases], local = [mit_waiter, mit_waiter_counter, mit_waiter_hclk_mhz])]
+    async fn mit_waiter_task(mut cx: mit_waiter_task::Context) -> ! {
+        loop {
+            cx.local.mit_waiter.wait().await;
+            let latency = cycles_to_ns(cx.local.mit_waiter_counter.read(), *cx.local.mit_waiter_hclk_mhz);
+            cx.shared.mit_releases.lock(|(releases, wc_release)| {
+                *releases += 1;
+                *wc_release = (*wc_release).max(latency);
+            });
+        }
+    }
+
+    // Signals a pair of activations every 100 ms, the second one deferred by the MIT
+    #[task(priority = 2, local = [deferred_wait_signaler])]
+    async fn deferred_wait_signaler_task(cx: deferred_wait_signaler_task::Context) -> ! {
+        cx.local.deferred_wait_signaler.set_min_inter_arrival(DEFERRED_WAIT_MIT, MitPolicy::Defer);
+        loop {
+            defmt::unwrap!(cx.local.deferred_wait_signaler.signal());
+            defmt::unwrap!(cx.local.deferred_wait_signaler.signal());
+
+            Mono::delay(Duration::millis(100)).await;
+        }
+    }
+
+    // Times out on the deferred release of each pair, which must still be pending afterwards
+    #[task(priority = 1, local = [
+        deferred_wait_waiter,
+        deferred_wait_activation_count: u32 = 0,
+        min_deferred_separation_us: u64 = u64::MAX,
+    ])]
+    async fn deferred_wait_waiter_task(cx: deferred_wait_waiter_task::Context) -> ! {
+        loop {
+            cx.local.deferred_wait_waiter.wait().await;
+            let first = Mono::now();
+
+            // The second release is pending, but deferred beyond the timeout
+            if cx.local.deferred_wait_waiter.wait_timeout(DEFERRED_WAIT_MIT / 5).await.is_ok() {
+                defmt::panic!("Deferred release taken before the MIT");
+            }
+            if cx.local.deferred_wait_waiter.wait_timeout(DEFERRED_WAIT_MIT * 2).await.is_err() {
+                defmt::panic!("Deferred release lost by the expired timed wait");
+            }
+            let separation_us = u64::from((Mono::now() - first).to_micros());
+            *cx.local.min_deferred_separation_us = (*cx.local.min_deferred_separation_us).min(separation_us);
+
+            *cx.local.deferred_wait_activation_count += 1;
+            if *cx.local.deferred_wait_activation_count == WCET_THRESHOLD {
+                defmt::info!(
+                    "Deferred timed wait: {} deferred releases kept pending by their expired timed wait, min separation {} us (MIT {} us)",
+                    WCET_THRESHOLD, *cx.local.min_deferred_separation_us, DEFERRED_WAIT_MIT.to_micros(),
+                );
+                if end_of_window(cx.local.deferred_wait_activation_count, "End of deferred timed wait profiling.") {
+                    *cx.local.min_deferred_separation_us = u64::MAX;
+                }
+            }
+        }
+    }
+
+    // Adds two samples per period: the first procedure leaves the barrier closed, the second opens it
+    #[task(priority = 2, local = [
+        protected_procedure,
+        protected_procedure_counter,
+        protected_procedure_hclk_mhz,
+        protected_procedure_activation_count: u32 = 0,
+        wc_procedure_closed: u32 = 0,
+        wc_procedure_open: u32 = 0,
+    ])]
+    async fn protected_procedure_task(cx: protected_procedure_task::Context) -> ! {
+        let counter = *cx.local.protected_procedure_counter;
+        let hclk_mhz = *cx.local.protected_procedure_hclk_mhz;
+        loop {
+            counter.reset();
+            cx.local.protected_procedure.call(|samples| *samples += 1);
+            let closed = cycles_to_ns(counter.read(), hclk_mhz);
+
+            let open = critical_section::with(|_cs| {
+                counter.reset();
+                cx.local.protected_procedure.call(|samples| *samples += 1);
+                let cycles = counter.read();
+                counter.reset();
+                cycles
+            });
+            let open = cycles_to_ns(open, hclk_mhz);
+
+            *cx.local.wc_procedure_closed = (*cx.local.wc_procedure_closed).max(closed);
+            *cx.local.wc_procedure_open = (*cx.local.wc_procedure_open).max(open);
+
+            // The entry task reports and ends the window during this delay
+            *cx.local.protected_procedure_activation_count += 1;
+            if *cx.local.protected_procedure_activation_count == WCET_THRESHOLD {
+                defmt::info!(
+                    "WC protected procedure time: barrier closed {} ns, barrier opened {} ns",
+                    *cx.local.wc_procedure_closed, *cx.local.wc_procedure_open,
+                );
+                *cx.local.protected_procedure_activation_count = 0;
+                if cfg!(feature = "continuous-reset") {
+                    *cx.local.wc_procedure_closed = 0;
+                    *cx.local.wc_procedure_open = 0;
+                }
+            }
+
+            Mono::delay(Duration::secs(1)).await;
+        }
+    }
+
+    #[task(priority = 1, local = [
+        protected_entry_caller,
+        protected_entry_caller_counter,
+        protected_entry_caller_hclk_mhz,
+        protected_entry_activation_count: u32 = 0,
+        wc_protected_entry: u32 = 0,
+    ])]
+    async fn protected_entry_task(cx: protected_entry_task::Context) -> ! {
+        loop {
+            let samples = cx.local.protected_entry_caller.call(core::mem::take).await;
+            let cycles = cx.local.protected_entry_caller_counter.read();
+
+            let time = cycles_to_ns(cycles, *cx.local.protected_entry_caller_hclk_mhz);
+            defmt::info!("Protected entry wake-up time: {} ns (number of cycles: {}, {} samples)", time, cycles, samples);
+            defmt::info!("---------------------------------------------------");
+
+            *cx.local.wc_protected_entry = (*cx.local.wc_protected_entry).max(time);
+
+            *cx.local.protected_entry_activation_count += 1;
+            if *cx.local.protected_entry_activation_count == WCET_THRESHOLD {
+                defmt::info!("WC protected entry wake-up time: {} ns", *cx.local.wc_protected_entry);
+                if end_of_window(cx.local.protected_entry_activation_count, "End of protected entry profiling.") {
+                    *cx.local.wc_protected_entry = 0;
+                }
+            }
+        }
+    }
+
+    // Releases the first 1 to BROADCAST_WAITERS waiters in turn: the first four wait at priority 1,
+    // the others at priority 2
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 3, shared = [broadcast_latencies], local = [
+        broadcast_signaler,
+        broadcast_sweep_count: u32 = 0,
+        wc_broadcast_release: [u32; BROADCAST_WAITERS] = [0; BROADCAST_WAITERS],
+        wc_broadcast_latency: [[u32; BROADCAST_WAITERS]; BROADCAST_WAITERS] = [[0; BROADCAST_WAITERS]; BROADCAST_WAITERS],
+    ])]
+    async fn broadcast_signaler_task(mut cx: broadcast_signaler_task::Context) -> ! {
+        let counter = get_cycle_counter();
+        let hclk_mhz = get_hclk_mhz();
+        loop {
+            for waiters in 1..=BROADCAST_WAITERS {
+                for index in 0..waiters {
+                    let spawned = match index {
+                        0 => broadcast_waiter_task_0::spawn(),
+                        1 => broadcast_waiter_task_1::spawn(),
+                        2 => broadcast_waiter_task_2::spawn(),
+                        3 => broadcast_waiter_task_3::spawn(),
+                        4 => broadcast_waiter_task_4::spawn(),
+                        5 => broadcast_waiter_task_5::spawn(),
+                        6 => broadcast_waiter_task_6::spawn(),
+                        _ => broadcast_waiter_task_7::spawn(),
+                    };
+                    if spawned.is_err() {
+                        defmt::panic!("Broadcast waiter {} still waiting", index);
+                    }
+                }
+                // The waiters start waiting during this delay
+                Mono::delay(Duration::millis(5)).await;
+
+                let (released, cycles) = critical_section::with(|_cs| {
+                    counter.reset();
+                    let released = cx.local.broadcast_signaler.signal();
+                    let cycles = counter.read();
+                    counter.reset();
+                    (released, cycles)
+                });
+                if released as usize != waiters {
+                    defmt::panic!("Broadcast released {} of {} waiters", released, waiters);
+                }
+                let wc_release = &mut cx.local.wc_broadcast_release[waiters - 1];
+                *wc_release = (*wc_release).max(cycles_to_ns(cycles, hclk_mhz));
+
+                // The released waiters run during this delay
+                Mono::delay(Duration::millis(5)).await;
+                let latencies = cx.shared.broadcast_latencies.lock(|latencies| *latencies);
+                for (wc, latency) in cx.local.wc_broadcast_latency[waiters - 1].iter_mut().zip(&latencies[..waiters]) {
+                    *wc = (*wc).max(cycles_to_ns(*latency, hclk_mhz));
+                }
+            }
+
+            *cx.local.broadcast_sweep_count += 1;
+            if *cx.local.broadcast_sweep_count == WCET_THRESHOLD {
+                for waiters in 1..=BROADCAST_WAITERS {
+                    defmt::info!(
+                        "Broadcast to {} waiters: WC release time {} ns, WC wake latency by waiter {} ns",
+                        waiters,
+                        cx.local.wc_broadcast_release[waiters - 1],
+                        &cx.local.wc_broadcast_latency[waiters - 1][..waiters],
+                    );
+                }
+                if end_of_window(cx.local.broadcast_sweep_count, "End of broadcast profiling.") {
+                    *cx.local.wc_broadcast_release = [0; BROADCAST_WAITERS];
+                    *cx.local.wc_broadcast_latency = [[0; BROADCAST_WAITERS]; BROADCAST_WAITERS];
+                }
+            }
+        }
+    }
+
+    // An RTIC task runs one instance at a time: each concurrent waiter needs its own task, all
+    // sharing the body of `wait_broadcast`
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 1, shared = [broadcast_latencies])]
+    async fn broadcast_waiter_task_0(cx: broadcast_waiter_task_0::Context) {
+        wait_broadcast(0, cx.shared.broadcast_latencies).await;
+    }
+
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 1, shared = [broadcast_latencies])]
+    async fn broadcast_waiter_task_1(cx: broadcast_waiter_task_1::Context) {
+        wait_broadcast(1, cx.shared.broadcast_latencies).await;
+    }
+
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 1, shared = [broadcast_latencies])]
+    async fn broadcast_waiter_task_2(cx: broadcast_waiter_task_2::Context) {
+        wait_broadcast(2, cx.shared.broadcast_latencies).await;
+    }
+
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 1, shared = [broadcast_latencies])]
+    async fn broadcast_waiter_task_3(cx: broadcast_waiter_task_3::Context) {
+        wait_broadcast(3, cx.shared.broadcast_latencies).await;
+    }
+
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 2, shared = [broadcast_latencies])]
+    async fn broadcast_waiter_task_4(cx: broadcast_waiter_task_4::Context) {
+        wait_broadcast(4, cx.shared.broadcast_latencies).await;
+    }
+
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 2, shared = [broadcast_latencies])]
+    async fn broadcast_waiter_task_5(cx: broadcast_waiter_task_5::Context) {
+        wait_broadcast(5, cx.shared.broadcast_latencies).await;
+    }
+
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 2, shared = [broadcast_latencies])]
+    async fn broadcast_waiter_task_6(cx: broadcast_waiter_task_6::Context) {
+        wait_broadcast(6, cx.shared.broadcast_latencies).await;
+    }
+
+    #[cfg(feature = "broadcast")]
+    #[task(priority = 2, shared = [broadcast_latencies])]
+    async fn broadcast_waiter_task_7(cx: broadcast_waiter_task_7::Context) {
+        wait_broadcast(7, cx.shared.broadcast_latencies).await;
+    }
+
+    // Signals back to back, for a window of SysTick interrupts, nothing and each primitive in turn,
+    // measuring the cost of each signal and the SysTick entry latency
+    #[cfg(feature = "lock-free-signal")]
+    #[task(priority = 1, shared = [signal_cost_releases], local = [
+        signal_cost_semaphore_signaler,
+        signal_cost_lock_free_signaler,
+        signal_cost_queue_signaler,
+        signal_cost_lock_free_queue_signaler,
+        signal_cost_lock_free_watchdogs,
+        signal_cost_activation_count: u32 = 0,
+        // Worst-case signal time of each primitive
+        wc_signal: [u32; SIGNAL_COST_PRIMITIVES] = [0; SIGNAL_COST_PRIMITIVES],
+        // Worst-case SysTick entry latency while idle, then signalling each primitive
+        wc_entry_latency: [u32; SIGNAL_COST_PRIMITIVES + 1] = [0; SIGNAL_COST_PRIMITIVES + 1],
+    ])]
+    async fn signal_cost_task(mut cx: signal_cost_task::Context) -> ! {
+        let counter = get_cycle_counter();
+        let hclk_mhz = get_hclk_mhz();
+        let window = Duration::millis(10);
+        loop {
+            // Signal cost with the interrupts enabled, as the lock-free primitives are meant to
+            // run, the waiters held off by the lock: the samples disturbed by SysTick are discarded
+            let releases = cx.shared.signal_cost_releases.lock(|releases| *releases);
+            let signal_cycles = cx.shared.signal_cost_releases.lock(|_releases| {
+                let mut signal_cycles = [None; SIGNAL_COST_PRIMITIVES];
+                for (primitive, cycles) in signal_cycles.iter_mut().enumerate() {
+                    *cycles = cycles_without_systick(counter, || match primitive {
+                        0 => defmt::unwrap!(cx.local.signal_cost_semaphore_signaler.signal()),
+                        1 => cx.local.signal_cost_lock_free_signaler.signal(),
+                        2 => defmt::unwrap!(cx.local.signal_cost_queue_signaler.signal(0)),
+                        _ => defmt::unwrap!(cx.local.signal_cost_lock_free_queue_signaler.signal(0)),
+                    });
+                }
+                signal_cycles
+            });
+            for (wc, cycles) in cx.local.wc_signal.iter_mut().zip(signal_cycles) {
+                if let Some(cycles) = cycles {
+                    *wc = (*wc).max(cycles_to_ns(cycles, hclk_mhz));
+                }
+            }
+            // The waiters consume the releases above
+            Mono::delay(Duration::millis(1)).await;
+            let consumed = cx.shared.signal_cost_releases.lock(|consumed| *consumed);
+            if consumed.iter().zip(&releases).any(|(consumed, released)| consumed == released) {
+                defmt::panic!("Signal cost waiters not released");
+            }
+            if cx.local.signal_cost_lock_free_watchdogs.iter_mut().any(|watchdog| watchdog.latest().is_none()) {
+                defmt::panic!("Lock-free deadline watchdogs not written");
+            }
+
+            for (variant, wc_entry_latency) in cx.local.wc_entry_latency.iter_mut().enumerate() {
+                crate::irq_latency::reset_entry_latency();
+                let end = Mono::now() + window;
+                while Mono::now() < end {
+                    match variant {
+                        0 => core::hint::black_box(()),
+                        1 => defmt::unwrap!(cx.local.signal_cost_semaphore_signaler.signal()),
+                        2 => cx.local.signal_cost_lock_free_signaler.signal(),
+                        3 => defmt::unwrap!(cx.local.signal_cost_queue_signaler.signal(0)),
+                        _ => defmt::unwrap!(cx.local.signal_cost_lock_free_queue_signaler.signal(0)),
+                    }
+                }
+                let entry_latency = crate::irq_latency::entry_latency();
+                *wc_entry_latency = (*wc_entry_latency).max(cycles_to_ns(entry_latency.wc_cycles, hclk_mhz));
+            }
+
+            *cx.local.signal_cost_activation_count += 1;
+            if *cx.local.signal_cost_activation_count == WCET_THRESHOLD {
+                defmt::info!(
+                    "WC signal time: task semaphore {} ns, lock-free semaphore {} ns, event queue {} ns, lock-free event queue {} ns",
+                    cx.local.wc_signal[0],
+                    cx.local.wc_signal[1],
+                    cx.local.wc_signal[2],
+                    cx.local.wc_signal[3],
+                );
+                defmt::info!(
+                    "WC SysTick entry latency: idle {} ns, signalling the task semaphore {} ns, the lock-free semaphore {} ns, the event queue {} ns, the lock-free event queue {} ns",
+                    cx.local.wc_entry_latency[0],
+                    cx.local.wc_entry_latency[1],
+                    cx.local.wc_entry_latency[2],
+                    cx.local.wc_entry_latency[3],
+                    cx.local.wc_entry_latency[4],
+                );
+                // The waiters consume each release as signalled, none should find the previous pending
+                defmt::info!(
+                    "Overruns: lock-free semaphore {}, lock-free event queue {}",
+                    cx.local.signal_cost_lock_free_signaler.overrun_stats().overruns,
+                    cx.local.signal_cost_lock_free_queue_signaler.overrun_stats().overruns,
+                );
+                if end_of_window(cx.local.signal_cost_activation_count, "End of lock-free signal profiling.") {
+                    *cx.local.wc_signal = [0; SIGNAL_COST_PRIMITIVES];
+                    cx.local.signal_cost_lock_free_signaler.reset_overrun_stats();
+                    cx.local.signal_cost_lock_free_queue_signaler.reset_overrun_stats();
+                    *cx.local.wc_entry_latency = [0; SIGNAL_COST_PRIMITIVES + 1];
+                }
+            }
+        }
+    }
+
+    // The waiters preempt the signal cost task, to consume each release as signalled
+    #[cfg(feature = "lock-free-signal")]
+    #[task(priority = 2, shared = [signal_cost_releases], local = [signal_cost_semaphore_waiter])]
+    async fn signal_cost_semaphore_waiter_task(mut cx: signal_cost_semaphore_waiter_task::Context) -> ! {
+        loop {
+            cx.local.signal_cost_semaphore_waiter.wait().await;
+            cx.shared.signal_cost_releases.lock(|releases| releases[0] += 1);
+        }
+    }
+
+    #[cfg(feature = "lock-free-signal")]
+    #[task(priority = 2, shared = [signal_cost_releases], local = [signal_cost_lock_free_waiter])]
+    async fn signal_cost_lock_free_waiter_task(mut cx: signal_cost_lock_free_waiter_task::Context) -> ! {
+        loop {
+            cx.local.signal_cost_lock_free_waiter.wait().await;
+            cx.shared.signal_cost_releases.lock(|releases| releases[1] += 1);
+        }
+    }
+
+    #[cfg(feature = "lock-free-signal")]
+    #[task(priority = 2, shared = [signal_cost_releases], local = [signal_cost_queue_waiter])]
+    async fn signal_cost_queue_waiter_task(mut cx: signal_cost_queue_waiter_task::Context) -> ! {
+        loop {
+            core::hint::black_box(cx.local.signal_cost_queue_waiter.wait().await);
+            cx.shared.signal_cost_releases.lock(|releases| releases[2] += 1);
+        }
+    }
+
+    #[cfg(feature = "lock-free-signal")]
+    #[task(priority = 2, shared = [signal_cost_releases], local = [signal_cost_lock_free_queue_waiter])]
+    async fn signal_cost_lock_free_queue_waiter_task(mut cx: signal_cost_lock_free_queue_waiter_task::Context) -> ! {
+        loop {
+            core::hint::black_box(cx.local.signal_cost_lock_free_queue_waiter.wait().await);
+            cx.shared.signal_cost_releases.lock(|releases| releases[3] += 1);
+        }
+    }
+
+    // Signals each primitive once more than it can hold, checking the overrun it reports
+    #[cfg(feature = "activation-overrun")]
+    #[task(priority = 1, local = [
+        overrun_semaphore_waiter,
+        overrun_semaphore_signaler,
+        overrun_queue_waiter,
+        overrun_queue_signaler,
+        activation_overrun_count: u32 = 0,
+        wc_semaphore_overrun: u32 = 0,
+        wc_queue_overrun: u32 = 0,
+    ])]
+    async fn activation_overrun_task(cx: activation_overrun_task::Context) -> ! {
+        let counter = get_cycle_counter();
+        let hclk_mhz = get_hclk_mhz();
+        defmt::info!("Start of activation overrun profiling.");
+        loop {
+            Mono::delay(Duration::millis(10)).await;
+
+            // Binary task semaphore: the second activation finds the first release pending
+            defmt::unwrap!(cx.local.overrun_semaphore_signaler.signal());
+            let start = counter.read();
+            let signaled = cx.local.overrun_semaphore_signaler.signal();
+            let semaphore_cycles = counter.read().wrapping_sub(start);
+            if !matches!(signaled, Err(crate::task_semaphore::SignalError::Overrun)) {
+                defmt::panic!("Task semaphore overrun not reported");
+            }
+            cx.local.overrun_semaphore_waiter.wait().await;
+
+            // Event queue dropping the oldest event: the event past its capacity drops the first one
+            for evt in 0..OVERRUN_QUEUE_CAPACITY as u32 {
+                defmt::unwrap!(cx.local.overrun_queue_signaler.signal(evt));
+            }
+            let start = counter.read();
+            let signaled = cx.local.overrun_queue_signaler.signal(OVERRUN_QUEUE_CAPACITY as u32);
+            let queue_cycles = counter.read().wrapping_sub(start);
+            if !matches!(signaled, Err(crate::event_queue::SignalError::Overrun(0))) {
+                defmt::panic!("Event queue overrun not reported with the dropped event");
+            }
+            for expected in 1..=OVERRUN_QUEUE_CAPACITY as u32 {
+                let evt = cx.local.overrun_queue_waiter.wait().await;
+                if evt != expected {
+                    defmt::panic!("Event queue received {}, expected {}", evt, expected);
+                }
+            }
+
+            *cx.local.wc_semaphore_overrun = (*cx.local.wc_semaphore_overrun).max(cycles_to_ns(semaphore_cycles, hclk_mhz));
+            *cx.local.wc_queue_overrun = (*cx.local.wc_queue_overrun).max(cycles_to_ns(queue_cycles, hclk_mhz));
+
+            *cx.local.activation_overrun_count += 1;
+            if *cx.local.activation_overrun_count == WCET_THRESHOLD {
+                defmt::info!(
+                    "WC overrunning signal time: task semaphore {} ns ({} overruns), event queue {} ns ({} overruns, {} events dropped)",
+                    *cx.local.wc_semaphore_overrun,
+                    cx.local.overrun_semaphore_signaler.overrun_stats().overruns,
+                    *cx.local.wc_queue_overrun,
+                    cx.local.overrun_queue_signaler.overrun_stats().overruns,
+                    cx.local.overrun_queue_waiter.dropped(),
+                );
+                if end_of_window(cx.local.activation_overrun_count, "End of activation overrun profiling.") {
+                    *cx.local.wc_semaphore_overrun = 0;
+                    *cx.local.wc_queue_overrun = 0;
+                    cx.local.overrun_semaphore_signaler.reset_overrun_stats();
+                    cx.local.overrun_queue_signaler.reset_overrun_stats();
+                }
             }
         }
     }
 
-    #[task(priority = 2, local = [spawn_overhead_dwt, spawn_overhead_cycles, spawn_overhead_ns, bc_spawn_overhead, spawn_overhead_hclk_mhz, spawn_overhead_activation_count])]
+    #[task(priority = 2, local = [spawn_overhead_counter, spawn_overhead_cycles, spawn_overhead_ns, bc_spawn_overhead, spawn_overhead_hclk_mhz, spawn_overhead_activation_count])]
     async fn spawn_overhead_task(cx: spawn_overhead_task::Context) -> ! {
         loop {
-            unsafe { cx.local.spawn_overhead_dwt.cyccnt.write(0) };
+            cx.local.spawn_overhead_counter.reset();
             spawned_task::spawn().unwrap();
-            *cx.local.spawn_overhead_cycles = cx.local.spawn_overhead_dwt.cyccnt.read();
+            *cx.local.spawn_overhead_cycles = cx.local.spawn_overhead_counter.read();
 
-            *cx.local.spawn_overhead_ns = (*cx.local.spawn_overhead_cycles as f32 / *cx.local.spawn_overhead_hclk_mhz) * 1000.0;
-            defmt::info!("Spawn overhead time: {} ns (number of cycles: {})", *cx.local.spawn_overhead_ns as u32, *cx.local.spawn_overhead_cycles);
+            *cx.local.spawn_overhead_ns = cycles_to_ns(*cx.local.spawn_overhead_cycles, *cx.local.spawn_overhead_hclk_mhz);
+            defmt::info!("Spawn overhead time: {} ns (number of cycles: {})", *cx.local.spawn_overhead_ns, *cx.local.spawn_overhead_cycles);
             defmt::info!("--------------------------------------------");
             
             // Update the wc_spawn_overhead
@@ -492,10 +1804,12 @@ mod app {
 
             *cx.local.spawn_overhead_activation_count += 1;
             if *cx.local.spawn_overhead_activation_count == WCET_THRESHOLD {
-                defmt::info!("BC spawn overhead time: {} ns", *cx.local.bc_spawn_overhead as u32);
-                defmt::panic!("End of spawn overhead profiling.");
+                defmt::info!("BC spawn overhead time: {} ns", *cx.local.bc_spawn_overhead);
+                if end_of_window(cx.local.spawn_overhead_activation_count, "End of spawn overhead profiling.") {
+                    *cx.local.bc_spawn_overhead = u32::MAX;
+                }
             }
-            Mono::delay((1 as u32).secs()).await;
+            Mono::delay(Duration::secs(1)).await;
         }
     }
 
@@ -504,15 +1818,15 @@ mod app {
         let _ = ();
     }
 
-    #[task(priority = 1, local = [context_switch_task_dwt, context_switch_task_cycles, context_switch_task_hclk_mhz, context_switch_ns, wc_context_switch, context_switch_task_activation_count])]
+    #[task(priority = 1, local = [context_switch_task_counter, context_switch_task_cycles, context_switch_task_hclk_mhz, context_switch_ns, wc_context_switch, context_switch_task_activation_count])]
     async fn context_switch_task(cx: context_switch_task::Context) -> ! {
         loop {
-            unsafe { cx.local.context_switch_task_dwt.cyccnt.write(0) };
+            cx.local.context_switch_task_counter.reset();
             preempting_task::spawn().unwrap();
-            *cx.local.context_switch_task_cycles = cx.local.context_switch_task_dwt.cyccnt.read();
+            *cx.local.context_switch_task_cycles = cx.local.context_switch_task_counter.read();
 
-            *cx.local.context_switch_ns = (*cx.local.context_switch_task_cycles as f32 / *cx.local.context_switch_task_hclk_mhz) * 1000.0;
-            defmt::info!("Context switch time: {} ns (number of cycles: {})", *cx.local.context_switch_ns as u32, *cx.local.context_switch_task_cycles);
+            *cx.local.context_switch_ns = cycles_to_ns(*cx.local.context_switch_task_cycles, *cx.local.context_switch_task_hclk_mhz);
+            defmt::info!("Context switch time: {} ns (number of cycles: {})", *cx.local.context_switch_ns, *cx.local.context_switch_task_cycles);
             defmt::info!("--------------------------------------------");
 
             // Update the wc_context_switch
@@ -520,11 +1834,13 @@ mod app {
 
             *cx.local.context_switch_task_activation_count += 1;
             if *cx.local.context_switch_task_activation_count == WCET_THRESHOLD {
-                defmt::info!("WC context switch time: {} ns", *cx.local.wc_context_switch as u32);
-                defmt::panic!("End of context switch profiling.");
+                defmt::info!("WC context switch time: {} ns", *cx.local.wc_context_switch);
+                if end_of_window(cx.local.context_switch_task_activation_count, "End of context switch profiling.") {
+                    *cx.local.wc_context_switch = 0;
+                }
             }
 
-            Mono::delay((1 as u32).secs()).await;
+            Mono::delay(Duration::secs(1)).await;
         }
     }
 
@@ -0,0 +1,19 @@
+use crate::time::Instant;
+
+/// Activations signalled while the previous one was still pending, since the last reset.
+#[derive(Clone, Copy, Default)]
+pub struct OverrunStats {
+    pub overruns: u32,
+    /// Instant of the first overrun
+    pub first: Option<Instant>,
+    /// Instant of the last overrun
+    pub last: Option<Instant>,
+}
+
+impl OverrunStats {
+    pub(crate) fn record(&mut self, now: Instant) {
+        self.overruns += 1;
+        self.first.get_or_insert(now);
+        self.last = Some(now);
+    }
+}
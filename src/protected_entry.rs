@@ -0,0 +1,112 @@
+use core::cell::RefCell;
+use critical_section::Mutex;
+use rtic_sync::signal::{Signal, SignalReader, SignalWriter};
+
+/// Ravenscar protected object with a single entry, guarded by a barrier over its data.
+///
+/// Procedures, called from tasks or interrupt handlers, update the data and re-evaluate the
+/// barrier within the same critical section. The entry caller is released once the barrier is
+/// open, and its body then runs within the critical section in which the barrier was found open.
+///
+/// Create statically allocated instances with [`make_protected_entry!`](crate::make_protected_entry).
+pub struct ProtectedEntry<T> {
+    data: Mutex<RefCell<T>>,
+    wake: Signal<()>,
+}
+
+impl<T> ProtectedEntry<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            data: Mutex::new(RefCell::new(data)),
+            wake: Signal::new(),
+        }
+    }
+
+    pub fn split(&self, barrier: fn(&T) -> bool) -> (EntryCaller<'_, T>, ProtectedProcedure<'_, T>) {
+        let (writer, reader) = self.wake.split();
+
+        (
+            EntryCaller {
+                data: &self.data,
+                barrier,
+                inner: reader,
+            },
+            ProtectedProcedure {
+                data: &self.data,
+                barrier,
+                inner: writer,
+            },
+        )
+    }
+}
+
+/// Creates a statically allocated [`ProtectedEntry`] holding `$init` of type `$T`, guarded by
+/// `$barrier`, and splits it, in the style of `rtic_sync::make_signal!`.
+#[macro_export]
+macro_rules! make_protected_entry {
+    ($T:ty, $init:expr, $barrier:expr) => {{
+        static PROTECTED_ENTRY: $crate::protected_entry::ProtectedEntry<$T> =
+            $crate::protected_entry::ProtectedEntry::new($init);
+        PROTECTED_ENTRY.split($barrier)
+    }};
+}
+
+pub struct EntryCaller<'a, T> {
+    data: &'a Mutex<RefCell<T>>,
+    barrier: fn(&T) -> bool,
+    inner: SignalReader<'a, ()>,
+}
+
+impl<'a, T> EntryCaller<'a, T> {
+    /// Waits for the barrier to be open, then runs `body` on the protected data.
+    pub async fn call<R>(&mut self, body: impl FnOnce(&mut T) -> R) -> R {
+        let mut body = Some(body);
+        // The signal only wakes the caller up: a wake-up left over by a procedure whose barrier
+        // was closed again finds it closed and waits again.
+        loop {
+            let result = critical_section::with(|cs| {
+                let mut data = self.data.borrow_ref_mut(cs);
+                if (self.barrier)(&data) {
+                    body.take().map(|body| body(&mut data))
+                } else {
+                    None
+                }
+            });
+            if let Some(result) = result {
+                return result;
+            }
+            self.inner.wait().await;
+        }
+    }
+}
+
+pub struct ProtectedProcedure<'a, T> {
+    data: &'a Mutex<RefCell<T>>,
+    barrier: fn(&T) -> bool,
+    inner: SignalWriter<'a, ()>,
+}
+
+impl<'a, T> Clone for ProtectedProcedure<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data,
+            barrier: self.barrier,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, T> ProtectedProcedure<'a, T> {
+    /// Runs `procedure` on the protected data, releasing the entry caller if it opens the
+    /// barrier.
+    pub fn call<R>(&mut self, procedure: impl FnOnce(&mut T) -> R) -> R {
+        critical_section::with(|cs| {
+            let mut data = self.data.borrow_ref_mut(cs);
+            let result = procedure(&mut data);
+            if (self.barrier)(&data) {
+                self.inner.write(());
+            }
+            result
+        })
+    }
+}
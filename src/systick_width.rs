@@ -0,0 +1,103 @@
+use crate::time::{get_cycle_counter, Mono};
+use core::cell::{Cell, RefCell};
+use critical_section::Mutex;
+use profiled_rtic_monotonics::profiler::{InterruptKind, MonotonicProfiler};
+use profiled_rtic_monotonics::Monotonic;
+
+/// Width of the tick counter of the SysTick monotonic, selected by the `systick-64bit` feature:
+/// each width is measured by its own build.
+pub const COUNTER_BITS: u32 = if cfg!(feature = "systick-64bit") { 64 } else { 32 };
+
+/// Execution time of an operation, in cycles of the board counter.
+#[derive(Clone, Copy)]
+pub struct CycleStats {
+    /// Number of measured executions
+    pub count: u32,
+    /// Sum of the execution times, to compute the mean
+    pub total_cycles: u64,
+    /// Worst-case execution time
+    pub wc_cycles: u32,
+}
+
+impl CycleStats {
+    const fn new() -> Self {
+        Self {
+            count: 0,
+            total_cycles: 0,
+            wc_cycles: 0,
+        }
+    }
+
+    fn record(&mut self, cycles: u32) {
+        self.count += 1;
+        self.total_cycles += cycles as u64;
+        self.wc_cycles = self.wc_cycles.max(cycles);
+    }
+
+    /// Mean execution time, 0 without executions.
+    pub fn mean_cycles(&self) -> u32 {
+        match self.count {
+            0 => 0,
+            count => (self.total_cycles / count as u64) as u32,
+        }
+    }
+}
+
+/// Execution times measured on the SysTick monotonic.
+#[derive(Clone, Copy)]
+pub struct WidthCosts {
+    /// `Mono::now()`, reading the tick counter
+    pub now: CycleStats,
+    /// Whole SysTick handler, incrementing the tick counter
+    pub handler: CycleStats,
+}
+
+impl WidthCosts {
+    const fn new() -> Self {
+        Self {
+            now: CycleStats::new(),
+            handler: CycleStats::new(),
+        }
+    }
+}
+
+static ENTRY_CYCLES: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+static WIDTH_COSTS: Mutex<RefCell<WidthCosts>> = Mutex::new(RefCell::new(WidthCosts::new()));
+
+/// SysTick profiler measuring the execution time of the handler, without logging.
+pub struct HandlerCostProfiler;
+
+impl MonotonicProfiler for HandlerCostProfiler {
+    fn on_interrupt_enter(_kind: InterruptKind) {
+        let cycles = get_cycle_counter().read();
+        critical_section::with(|cs| ENTRY_CYCLES.borrow(cs).set(cycles));
+    }
+
+    fn on_interrupt_exit(_kind: InterruptKind) {
+        let cycles = get_cycle_counter().read();
+        critical_section::with(|cs| {
+            let elapsed = cycles.wrapping_sub(ENTRY_CYCLES.borrow(cs).get());
+            WIDTH_COSTS.borrow_ref_mut(cs).handler.record(elapsed);
+        });
+    }
+}
+
+/// Calls `Mono::now()`, measuring its execution time.
+pub fn measure_now() {
+    let counter = get_cycle_counter();
+    let start = counter.read();
+    core::hint::black_box(Mono::now());
+    let elapsed = counter.read().wrapping_sub(start);
+
+    critical_section::with(|cs| WIDTH_COSTS.borrow_ref_mut(cs).now.record(elapsed));
+}
+
+/// Execution times measured since the last reset.
+pub fn width_costs() -> WidthCosts {
+    critical_section::with(|cs| *WIDTH_COSTS.borrow_ref(cs))
+}
+
+/// Restarts the measurement of the execution times.
+pub fn reset_width_costs() {
+    critical_section::with(|cs| *WIDTH_COSTS.borrow_ref_mut(cs) = WidthCosts::new());
+}
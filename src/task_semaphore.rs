@@ -1,64 +1,243 @@
-use crate::time::{Instant, Mono};
-use core::{
-    mem::MaybeUninit,
-    sync::atomic::{AtomicBool, Ordering},
-};
+use crate::inter_arrival::{DeferredMit, MinInterArrival, MitPolicy, MitStats, MitViolation, ReleaseSpacing};
+use crate::overrun::OverrunStats;
+use crate::time::{Duration, Instant, Mono, TimeoutError};
+use core::cell::Cell;
+use critical_section::Mutex;
 use rtic_monotonics::Monotonic;
 use rtic_sync::signal::{Signal, SignalReader, SignalWriter};
 
-pub struct TaskSemaphore;
-
-static mut TASK_SEMAPHORE: MaybeUninit<Signal<()>> = MaybeUninit::uninit();
-static INITIALIZED: AtomicBool = AtomicBool::new(false);
+/// Suspension object releasing one waiting task.
+///
+/// Create statically allocated instances with [`make_task_semaphore!`](crate::make_task_semaphore).
+pub struct TaskSemaphore {
+    signal: Signal<()>,
+    /// Pending releases, at most one in binary mode
+    count: Mutex<Cell<u32>>,
+    /// Releases dropped because `count` was at its maximum
+    dropped: Mutex<Cell<u32>>,
+    deferred_mit: DeferredMit,
+}
 
 impl TaskSemaphore {
-    // The hint is safe since the implementation never leaks the reference out and its used atomically
-    #[allow(static_mut_refs)]
-    pub fn init(
+    pub const fn new() -> Self {
+        Self {
+            signal: Signal::new(),
+            count: Mutex::new(Cell::new(0)),
+            dropped: Mutex::new(Cell::new(0)),
+            deferred_mit: Mutex::new(Cell::new(None)),
+        }
+    }
+
+    /// Binary semaphore: the signals not yet consumed by the waiter collapse into one release.
+    pub fn split(
+        &self,
+        activation_watchdog: SignalWriter<'static, Instant>,
+    ) -> (TaskSemaphoreWaiter<'_>, TaskSemaphoreSignaler<'_>) {
+        self.split_with(None, activation_watchdog)
+    }
+
+    /// Counting semaphore: each signal adds one release, up to `max_count` pending releases,
+    /// and each wait consumes exactly one. The signals beyond `max_count` are dropped, without
+    /// writing `activation_watchdog`, and counted in [`TaskSemaphoreWaiter::dropped`].
+    pub fn split_counting(
+        &self,
+        max_count: u32,
+        activation_watchdog: SignalWriter<'static, Instant>,
+    ) -> (TaskSemaphoreWaiter<'_>, TaskSemaphoreSignaler<'_>) {
+        if max_count == 0 {
+            defmt::panic!("TaskSemaphore maximum count must be at least 1");
+        }
+        self.split_with(Some(max_count), activation_watchdog)
+    }
+
+    fn split_with(
+        &self,
+        max_count: Option<u32>,
         activation_watchdog: SignalWriter<'static, Instant>,
-    ) -> (TaskSemaphoreWaiter<'static>, TaskSemaphoreSignaler<'static>) {
-        let (writer, reader) = if INITIALIZED
-            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
-            .is_ok()
-        {
-            // SAFETY: The CAS operation guarantees at most one initialization even with competing threads, hence if we reach this branch we
-            // are guaranteed to be the only initializers of the static signal, and splitting is safe.
-            unsafe { TASK_SEMAPHORE.write(Signal::new()).split() }
-        } else {
-            defmt::panic!("Multiple TaskSemaphore initialization");
-        };
+    ) -> (TaskSemaphoreWaiter<'_>, TaskSemaphoreSignaler<'_>) {
+        let (writer, reader) = self.signal.split();
 
         (
-            TaskSemaphoreWaiter { inner: reader },
+            TaskSemaphoreWaiter {
+                inner: reader,
+                count: &self.count,
+                dropped: &self.dropped,
+                spacing: ReleaseSpacing::new(&self.deferred_mit),
+            },
             TaskSemaphoreSignaler {
                 inner: writer,
                 activation_watchdog,
+                count: &self.count,
+                dropped: &self.dropped,
+                max_count: max_count.unwrap_or(1),
+                deferred_mit: &self.deferred_mit,
+                min_inter_arrival: None,
+                overruns: OverrunStats::default(),
+                overrun_error: false,
             },
         )
     }
 }
 
+impl Default for TaskSemaphore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Creates a statically allocated [`TaskSemaphore`] and splits it, in the style of
+/// `rtic_sync::make_signal!`.
+///
+/// Each call site allocates its own instance, whose activations are written to
+/// `activation_watchdog`. Add `max_count = N` for a counting semaphore.
+#[macro_export]
+macro_rules! make_task_semaphore {
+    ($activation_watchdog:expr) => {{
+        static TASK_SEMAPHORE: $crate::task_semaphore::TaskSemaphore = $crate::task_semaphore::TaskSemaphore::new();
+        TASK_SEMAPHORE.split($activation_watchdog)
+    }};
+    ($activation_watchdog:expr, max_count = $max_count:expr) => {{
+        static TASK_SEMAPHORE: $crate::task_semaphore::TaskSemaphore = $crate::task_semaphore::TaskSemaphore::new();
+        TASK_SEMAPHORE.split_counting($max_count, $activation_watchdog)
+    }};
+}
+
 pub struct TaskSemaphoreWaiter<'a> {
     inner: SignalReader<'a, ()>,
+    count: &'a Mutex<Cell<u32>>,
+    dropped: &'a Mutex<Cell<u32>>,
+    spacing: ReleaseSpacing<'a>,
 }
 
 impl<'a> TaskSemaphoreWaiter<'a> {
     pub async fn wait(&mut self) {
-        self.inner.wait().await;
+        // The signal only wakes the waiter up, the releases are taken from the count, in the
+        // same critical section as they are checked: a wake-up left over by releases already
+        // consumed finds the count at zero and waits again. A pending release is only taken
+        // once deferred: a timed wait expiring meanwhile leaves it pending.
+        loop {
+            if critical_section::with(|cs| self.count.borrow(cs).get() > 0) {
+                self.spacing.release().await;
+                let released = critical_section::with(|cs| {
+                    let count = self.count.borrow(cs);
+                    let pending = count.get();
+                    if pending > 0 {
+                        count.set(pending - 1);
+                    }
+                    pending > 0
+                });
+                if released {
+                    return;
+                }
+            }
+            self.inner.wait().await;
+        }
+    }
+
+    /// Waits for a release until `instant`, removing the timer queue entry if released first.
+    pub async fn wait_until(&mut self, instant: Instant) -> Result<(), TimeoutError> {
+        Mono::timeout_at(instant, self.wait()).await.map_err(|_| TimeoutError)
+    }
+
+    /// Waits for a release for at most `duration`, see [`wait_until`](Self::wait_until).
+    pub async fn wait_timeout(&mut self, duration: Duration) -> Result<(), TimeoutError> {
+        Mono::timeout_after(duration, self.wait()).await.map_err(|_| TimeoutError)
+    }
+
+    /// Number of releases dropped by a counting semaphore at its maximum count.
+    pub fn dropped(&self) -> u32 {
+        critical_section::with(|cs| self.dropped.borrow(cs).get())
     }
 }
 
 pub struct TaskSemaphoreSignaler<'a> {
     inner: SignalWriter<'a, ()>,
     activation_watchdog: SignalWriter<'static, Instant>,
+    count: &'a Mutex<Cell<u32>>,
+    dropped: &'a Mutex<Cell<u32>>,
+    max_count: u32,
+    deferred_mit: &'a DeferredMit,
+    min_inter_arrival: Option<MinInterArrival>,
+    overruns: OverrunStats,
+    overrun_error: bool,
+}
+
+/// Activation rejected or reported by [`TaskSemaphoreSignaler::signal`].
+#[derive(Clone, Copy, defmt::Format)]
+pub enum SignalError {
+    /// The activation arrived too early and was dropped, see [`MitPolicy::Reject`].
+    MitViolation(MitViolation),
+    /// The activation found no room for its release: a binary semaphore already released, or a
+    /// counting one at its maximum count. See [`TaskSemaphoreSignaler::set_overrun_error`].
+    Overrun,
 }
 
 impl<'a> TaskSemaphoreSignaler<'a> {
-    pub fn signal(&mut self) {
-        critical_section::with(|_cs| {
-            self.inner.write(());
-            // Signal activation to the related deadline watchdog
-            self.activation_watchdog.write(Mono::now());
+    /// Checks the activations against the minimum inter-arrival time `mit`, handling the early
+    /// ones according to `policy`.
+    pub fn set_min_inter_arrival(&mut self, mit: Duration, policy: MitPolicy) {
+        self.min_inter_arrival = Some(MinInterArrival::new(mit, policy));
+        let deferred_mit = (policy == MitPolicy::Defer).then_some(mit);
+        critical_section::with(|cs| self.deferred_mit.borrow(cs).set(deferred_mit));
+    }
+
+    /// Minimum inter-arrival time counters, if checked.
+    pub fn mit_stats(&self) -> Option<MitStats> {
+        self.min_inter_arrival.as_ref().map(MinInterArrival::stats)
+    }
+
+    pub fn reset_mit_stats(&mut self) {
+        if let Some(min_inter_arrival) = &mut self.min_inter_arrival {
+            min_inter_arrival.reset_stats();
+        }
+    }
+
+    /// Makes [`signal`](Self::signal) return an error on overruns, besides counting them.
+    pub fn set_overrun_error(&mut self, enabled: bool) {
+        self.overrun_error = enabled;
+    }
+
+    /// Activations that found no room for their release: signalled while a binary semaphore was
+    /// already released, or while a counting one was at its maximum count.
+    pub fn overrun_stats(&self) -> OverrunStats {
+        self.overruns
+    }
+
+    pub fn reset_overrun_stats(&mut self) {
+        self.overruns = OverrunStats::default();
+    }
+
+    /// Signals an activation, unless rejected by the minimum inter-arrival time check.
+    pub fn signal(&mut self) -> Result<(), SignalError> {
+        critical_section::with(|cs| {
+            let now = Mono::now();
+            if let Some(min_inter_arrival) = &mut self.min_inter_arrival {
+                min_inter_arrival.check(now).map_err(SignalError::MitViolation)?;
+            }
+            let count = self.count.borrow(cs);
+            let overrun = count.get() == self.max_count;
+            // A counting semaphore drops the activation: no release to wake up for, nor deadline
+            // to watch. A binary one collapses it into the pending release.
+            let dropped = overrun && self.max_count > 1;
+            if !overrun {
+                count.set(count.get() + 1);
+            } else if dropped {
+                let dropped_count = self.dropped.borrow(cs);
+                dropped_count.set(dropped_count.get() + 1);
+            }
+            if !dropped {
+                self.inner.write(());
+                // Signal activation to the related deadline watchdog
+                self.activation_watchdog.write(now);
+            }
+
+            if overrun {
+                self.overruns.record(now);
+                if self.overrun_error {
+                    return Err(SignalError::Overrun);
+                }
+            }
+            Ok(())
         })
     }
 }
@@ -1,48 +1,115 @@
+use crate::board::CycleCounter;
 use rtic_monotonics::Monotonic;
-#[cfg(feature = "systick")]
-use cortex_m::peripheral::DWT;
 
-#[cfg(feature = "systick")]
-static mut HCLK_MHZ: f32 = 0.0;
+// The features selecting the monotonic are listed in `build.rs`, which sets the cfgs used here
+#[cfg(multiple_monos)]
+compile_error!("Several features select the monotonic (dwt-mono or an overhead selecting its own), enable only one");
 
-#[cfg(feature = "systick")]
-pub fn set_hclk_mhz(hclk_mhz: f32) {
-    unsafe {
-        HCLK_MHZ = hclk_mhz;
-    }
+/// Core clock frequency in MHz, the rate of the board cycle counter, as configured by
+/// `board::setup`.
+#[cfg(not(feature = "dwt-mono"))]
+pub fn get_hclk_mhz() -> u32 {
+    crate::board::CORE_CLOCK_HZ / 1_000_000
 }
 
-#[cfg(feature = "systick")]
-fn get_hclk_mhz() -> f32 {
-    unsafe { HCLK_MHZ }
+/// The monotonic counts core clock cycles, its rate is the core clock.
+#[cfg(feature = "dwt-mono")]
+pub fn get_hclk_mhz() -> u32 {
+    Duration::micros(1).ticks() as u32
 }
 
-#[cfg(feature = "systick")]
-static mut DWT_REF: Option<&'static DWT> = None;
+/// Converts a number of core clock cycles into nanoseconds, without relying on an FPU.
+pub fn cycles_to_ns(cycles: u32, hclk_mhz: u32) -> u32 {
+    (cycles as u64 * 1_000 / hclk_mhz as u64) as u32
+}
 
-#[cfg(feature = "systick")]
-pub fn set_dwt_ref(dwt_ref: &'static DWT) {
-    unsafe {
-        DWT_REF = Some(dwt_ref);
-    }
+/// Converts nanoseconds into a number of core clock cycles, the inverse of [`cycles_to_ns`].
+pub fn ns_to_cycles(ns: u32, hclk_mhz: u32) -> u32 {
+    (ns as u64 * hclk_mhz as u64 / 1_000) as u32
 }
 
-#[cfg(feature = "systick")]
-fn get_dwt_ref() -> &'static DWT {
-    unsafe { DWT_REF.expect("DWT reference not set") }
+/// The board cycle counter, derived from `Mono::now()` with dwt-mono.
+pub fn get_cycle_counter() -> &'static CycleCounter {
+    &crate::board::CYCLE_COUNTER
 }
 
 
-#[cfg(not(feature = "systick"))]
+/// The board cycle counter, as seen by the profiled monotonics.
+#[cfg(overhead_mono)]
+pub enum BoardCycles {}
+
+#[cfg(overhead_mono)]
+impl profiled_rtic_monotonics::profiler::CycleSource for BoardCycles {
+    fn cycles() -> u32 {
+        get_cycle_counter().read()
+    }
+
+    fn rate_mhz() -> u32 {
+        get_hclk_mhz()
+    }
+}
+
+#[cfg(not(profiled_mono))]
 rtic_monotonics::systick_monotonic!(Mono, 1_000);
+#[cfg(feature = "dwt-mono")]
+profiled_rtic_monotonics::dwt_systick_monotonic!(Mono, crate::board::CORE_CLOCK_HZ);
 #[cfg(feature = "systick")]
-profiled_rtic_monotonics::systick_monotonic!(Mono, 1_000, get_hclk_mhz(), get_dwt_ref());
+profiled_rtic_monotonics::systick_monotonic!(
+    Mono,
+    1_000,
+    profiler = profiled_rtic_monotonics::systick::OverheadProfiler<BoardCycles>
+);
+#[cfg(feature = "systick-tickless")]
+profiled_rtic_monotonics::systick_tickless_monotonic!(
+    Mono,
+    1_000,
+    profiler = profiled_rtic_monotonics::systick_tickless::HandlerProfiler<BoardCycles>
+);
+#[cfg(feature = "wake-lateness")]
+profiled_rtic_monotonics::systick_monotonic!(
+    Mono,
+    1_000,
+    profiler = crate::wake_lateness::WakeLatenessProfiler
+);
+#[cfg(feature = "systick-width")]
+profiled_rtic_monotonics::systick_monotonic!(
+    Mono,
+    1_000,
+    profiler = crate::systick_width::HandlerCostProfiler
+);
+#[cfg(feature = "lock-free-signal")]
+profiled_rtic_monotonics::systick_monotonic!(
+    Mono,
+    1_000,
+    profiler = crate::irq_latency::EntryLatencyProfiler
+);
+#[cfg(feature = "tim2")]
+profiled_rtic_monotonics::stm32_tim2_monotonic!(
+    Mono,
+    1_000_000,
+    profiler = profiled_rtic_monotonics::stm32::Tim2Profiler<BoardCycles>
+);
 
 // defmt timestamp
+#[cfg(not(profiled_mono))]
 defmt::timestamp!("{=u32:ms}", Mono::now().duration_since_epoch().to_millis());
+#[cfg(fine_systick_mono)]
+defmt::timestamp!("{=u64:us}", Mono::now_fine().duration_since_epoch().to_micros());
+#[cfg(feature = "systick-tickless")]
+defmt::timestamp!("{=u64:ms}", Mono::now().duration_since_epoch().to_millis());
+#[cfg(any(feature = "tim2", feature = "dwt-mono"))]
+defmt::timestamp!("{=u64:us}", Mono::now().duration_since_epoch().to_micros());
 
-#[cfg(not(feature = "systick"))]
+#[cfg(not(profiled_mono))]
 pub type Instant = <Mono as rtic_monotonics::Monotonic>::Instant;
+#[cfg(not(profiled_mono))]
+pub type Duration = <Mono as rtic_monotonics::Monotonic>::Duration;
 
-#[cfg(feature = "systick")]
+#[cfg(profiled_mono)]
 pub type Instant = <Mono as profiled_rtic_monotonics::Monotonic>::Instant;
+#[cfg(profiled_mono)]
+pub type Duration = <Mono as profiled_rtic_monotonics::Monotonic>::Duration;
+
+/// A timed wait expired before the waiter was released.
+#[derive(Debug, defmt::Format)]
+pub struct TimeoutError;
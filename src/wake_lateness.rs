@@ -0,0 +1,63 @@
+use crate::time::{cycles_to_ns, get_cycle_counter, get_hclk_mhz, ns_to_cycles, Instant, Mono};
+use core::cell::Cell;
+use cortex_m::peripheral::SYST;
+use critical_section::Mutex;
+use profiled_rtic_monotonics::profiler::{InterruptKind, MonotonicProfiler};
+use rtic_monotonics::Monotonic;
+
+/// Timestamps of a wake-up released by the SysTick interrupt, in cycles of the board counter.
+#[derive(Clone, Copy)]
+pub struct Release {
+    /// Tick at which the waiter was released
+    pub tick: Instant,
+    /// Start of the tick, reconstructed from the SysTick current value
+    pub tick_cycles: u32,
+    /// End of the timer queue processing, which woke the waiter up
+    pub release_cycles: u32,
+}
+
+static TICK_CYCLES: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+static EXIT_CYCLES: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+static LAST_RELEASE: Mutex<Cell<Option<Release>>> = Mutex::new(Cell::new(None));
+
+/// Frequency of the SysTick clock, in MHz, given by its reload value: SysTick counts
+/// `reload + 1` cycles per tick of the monotonic.
+fn systick_mhz() -> u32 {
+    (SYST::get_reload() + 1) / Mono::TICK_PERIOD.to_micros() as u32
+}
+
+/// SysTick profiler timestamping the interrupts that release a waiter.
+pub struct WakeLatenessProfiler;
+
+impl MonotonicProfiler for WakeLatenessProfiler {
+    fn on_interrupt_enter(_kind: InterruptKind) {
+        let cycles = get_cycle_counter().read();
+        // SysTick counts down from the reload value, converted to cycles of the board counter
+        let since_tick = SYST::get_reload() - SYST::get_current();
+        let since_tick = ns_to_cycles(cycles_to_ns(since_tick, systick_mhz()), get_hclk_mhz());
+        critical_section::with(|cs| TICK_CYCLES.borrow(cs).set(cycles.wrapping_sub(since_tick)));
+    }
+
+    fn on_interrupt_exit(_kind: InterruptKind) {
+        let cycles = get_cycle_counter().read();
+        critical_section::with(|cs| EXIT_CYCLES.borrow(cs).set(cycles));
+    }
+
+    fn on_wake(released: u32) {
+        if released > 0 {
+            let tick = Mono::now();
+            critical_section::with(|cs| {
+                LAST_RELEASE.borrow(cs).set(Some(Release {
+                    tick,
+                    tick_cycles: TICK_CYCLES.borrow(cs).get(),
+                    release_cycles: EXIT_CYCLES.borrow(cs).get(),
+                }))
+            });
+        }
+    }
+}
+
+/// Takes the last wake-up released by the SysTick interrupt, if any.
+pub fn take_release() -> Option<Release> {
+    critical_section::with(|cs| LAST_RELEASE.borrow(cs).take())
+}